@@ -0,0 +1,138 @@
+// Timings of the verifiers against each other. They are ignored by default, as a debug build says
+// little about speed; run them with `cargo test --release -- --ignored --nocapture`.
+
+use std::time::{Duration, Instant};
+
+use polynomen::Poly;
+use zksnark::groth16::FrLocal;
+
+use crate::{
+    circuit::cubic_circuit,
+    crs_player::{ComputationSecrets, CrsPlayer},
+    groth16_backend::{verify_groth16, Groth16Key, Groth16Proof, Groth16Prover},
+    pairing_engine::ZksnarkEngine,
+    prover::{ComputationProver, Prover},
+    verificiation::{self, DesignatedVerifier},
+    ProvingKey, PublicData, VerifyingKey,
+};
+
+const RUNS: usize = 11;
+
+struct Timings {
+    median: Duration,
+    fastest: Duration,
+    slowest: Duration,
+}
+
+/// Runs `f` once to warm up, then times each of `runs` calls on its own. The results go through
+/// `black_box` so the calls are not optimised away, and the last one is returned.
+fn time_repeatedly<T>(runs: usize, mut f: impl FnMut() -> T) -> (T, Timings) {
+    assert!(runs > 0, "at least one timed run");
+    let mut result = std::hint::black_box(f());
+    let mut times: Vec<Duration> = vec![];
+    for _ in 0..runs {
+        let start = Instant::now();
+        result = std::hint::black_box(f());
+        times.push(start.elapsed());
+    }
+    times.sort();
+
+    let timings = Timings {
+        median: times[times.len() / 2],
+        fastest: times[0],
+        slowest: times[times.len() - 1],
+    };
+    return (result, timings);
+}
+
+fn report(name: &str, validation: bool, timings: &Timings) {
+    println!(
+        "{} returns {} in {:?} (fastest {:?}, slowest {:?} over {} runs)",
+        name, validation, timings.median, timings.fastest, timings.slowest, RUNS
+    );
+}
+
+/// The player of a one-player ceremony can verify without pairings, as it still knows `s` and `alpha`
+#[test]
+#[ignore]
+fn time_designated_and_pairing_verification() {
+    let (s, alpha) = (40, 41);
+    let target_polynomial: Poly<usize> = Poly::new_from_coeffs(&[3, 4, 5]);
+    let ratio_polynomial: Poly<usize> = Poly::new_from_coeffs(&[6, 7]);
+    let prover = Prover::new(&target_polynomial * &ratio_polynomial);
+
+    let public = PublicData {
+        target_polynomial,
+        crs: CrsPlayer::new(s, alpha).start_crs_ceremony().accumulator,
+    };
+    let proof = prover
+        .prove(&ProvingKey::new(&public))
+        .expect("degree within the CRS");
+    let verifying_key = VerifyingKey::new(&public).expect("target degree within the CRS");
+    let designated_verifier = DesignatedVerifier::new(&public.target_polynomial, s, alpha);
+
+    let (validation, timings) =
+        time_repeatedly(RUNS, || verificiation::verify(&verifying_key, &proof));
+    report("Pairing verification", validation, &timings);
+    assert!(validation);
+
+    let (validation, timings) = time_repeatedly(RUNS, || designated_verifier.verify(&proof));
+    report("Designated verification", validation, &timings);
+    assert!(validation);
+}
+
+/// The same statement verified with the Petkus-style protocol and with the zksnark crate's Groth16
+#[test]
+#[ignore]
+fn time_computation_and_groth16_verification() {
+    let circuit = cubic_circuit();
+    let qap = circuit.compile();
+    let out = circuit.variable_index("out").expect("out is a gate output");
+    let witness = circuit
+        .compute_witness(&[("x", 3)])
+        .expect("x is the circuit's input");
+    let public_inputs = [FrLocal::from(30)];
+
+    let (proving_key, verifying_key) = CrsPlayer::<ZksnarkEngine>::with_engine(31, 32)
+        .create_computation_keys(
+            &qap,
+            &[out],
+            &ComputationSecrets {
+                alpha_l: 33,
+                alpha_r: 34,
+                alpha_o: 35,
+                beta: 36,
+                gamma: 37,
+                rho_l: 38,
+                rho_r: 39,
+            },
+        )
+        .expect("valid public inputs");
+    let proof = ComputationProver::new(&qap, witness.clone())
+        .prove(&proving_key)
+        .expect("a satisfying witness");
+    let (validation, timings) = time_repeatedly(RUNS, || {
+        verificiation::verify_computation(&verifying_key, &public_inputs, &proof)
+    });
+    report("Computation proof verification", validation, &timings);
+    assert!(validation);
+
+    // the crate's verification consumes its key, so every run gets a key and proof of its own,
+    // made before the timing starts
+    let groth16_prover = Groth16Prover::new(witness);
+    let mut groth16_keys_and_proofs: Vec<(Groth16Key, Groth16Proof)> = (0..RUNS + 1)
+        .map(|_| {
+            let key = Groth16Key::new(&qap, &[out]).expect("valid public inputs");
+            let proof = groth16_prover.prove(&key);
+            return (key, proof);
+        })
+        .collect();
+    let (validation, timings) = time_repeatedly(RUNS, || {
+        let (key, proof) = groth16_keys_and_proofs
+            .pop()
+            .expect("one key and proof per run");
+        return verify_groth16(key, &public_inputs, proof);
+    });
+    report("Groth16 verification", validation, &timings);
+    assert!(validation);
+}
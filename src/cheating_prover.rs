@@ -0,0 +1,229 @@
+// Ways a prover without a valid polynomial might try to get a proof accepted, and which check of
+// `verify` stops each of them.
+
+use polynomen::Poly;
+
+use crate::{
+    pairing_engine::PairingEngine, prover::Prover, DegreeError, Proof, ProofError, ProvingKey,
+};
+
+/// A named attack on the protocol of section 3 of the paper
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum CheatingStrategy {
+    /// proves `t(x) h(x) + 1`, which lacks the roots of `t(x)`, dropping the remainder of the division
+    NotDivisibleByTarget,
+    /// proves a multiple of `t(x)` of higher degree than the CRS, evaluating only the terms it has
+    /// powers of `s` for
+    DegreeExceedingCrs,
+    /// sends encryptions of random values instead of evaluations
+    RandomGroupElements,
+    /// sends an honest proof for another target polynomial made with the same CRS
+    ReusedProofOfAnotherTarget,
+    /// proves `2 t(x) h(x) + 1` and doubles `[h(s)]` in the hope of making up for the difference
+    ScaledRatio,
+    /// sends `[p(s)]` in place of the alpha-shifted `[alpha p(s)]`
+    OmittedAlphaShift,
+}
+
+impl CheatingStrategy {
+    pub(crate) const ALL: [CheatingStrategy; 6] = [
+        CheatingStrategy::NotDivisibleByTarget,
+        CheatingStrategy::DegreeExceedingCrs,
+        CheatingStrategy::RandomGroupElements,
+        CheatingStrategy::ReusedProofOfAnotherTarget,
+        CheatingStrategy::ScaledRatio,
+        CheatingStrategy::OmittedAlphaShift,
+    ];
+}
+
+/// A prover that does not know a multiple of the target polynomial and follows `strategy` instead
+pub(crate) struct CheatingProver {
+    strategy: CheatingStrategy,
+}
+
+impl CheatingProver {
+    pub(crate) fn new(strategy: CheatingStrategy) -> Self {
+        Self { strategy }
+    }
+
+    /// Refuses, as `Prover::prove` does, a key with too few powers of s for the strategy's polynomials
+    pub(crate) fn prove<E: PairingEngine>(
+        &self,
+        key: &ProvingKey<Poly<usize>, E>,
+    ) -> Result<Proof<E>, ProofError> {
+        let t = &key.target_polynomial;
+        let h: Poly<usize> = Poly::new_from_coeffs(&[6, 7]);
+        let evaluate = |powers: &[E::G1], polynomial: &Poly<usize>| -> Result<E::G1, DegreeError> {
+            return E::homomorphic1_eval_polynomial(powers, polynomial);
+        };
+
+        return Ok(match self.strategy {
+            CheatingStrategy::NotDivisibleByTarget => {
+                let p = t * &h + Poly::new_from_coeffs(&[1]);
+                Proof {
+                    encrypted1_ratio_poly_at_s: evaluate(&key.encrypted1_s_powers, &h)?,
+                    encrypted1_secret_poly_at_s: evaluate(&key.encrypted1_s_powers, &p)?,
+                    encrypted1_alpha_times_secret_poly_at_s: evaluate(
+                        &key.encrypted1_alpha_times_s_powers,
+                        &p,
+                    )?,
+                }
+            }
+            CheatingStrategy::DegreeExceedingCrs => {
+                // `h(x) = 1 + x^n` puts p(x) one degree beyond the highest power of s in the CRS
+                let n = key.encrypted1_s_powers.len() - t.degree().unwrap_or(0);
+                let h =
+                    Poly::new_from_coeffs_iter((0..n + 1).map(|k| usize::from(k == 0 || k == n)));
+                let p = t * &h;
+                // the evaluation refuses terms beyond the powers of s, so the cheater drops them itself
+                let known_terms = |polynomial: &Poly<usize>| -> Poly<usize> {
+                    return Poly::new_from_coeffs_iter(
+                        polynomial
+                            .coeffs()
+                            .into_iter()
+                            .take(key.encrypted1_s_powers.len()),
+                    );
+                };
+                Proof {
+                    encrypted1_ratio_poly_at_s: evaluate(
+                        &key.encrypted1_s_powers,
+                        &known_terms(&h),
+                    )?,
+                    encrypted1_secret_poly_at_s: evaluate(
+                        &key.encrypted1_s_powers,
+                        &known_terms(&p),
+                    )?,
+                    encrypted1_alpha_times_secret_poly_at_s: evaluate(
+                        &key.encrypted1_alpha_times_s_powers,
+                        &known_terms(&p),
+                    )?,
+                }
+            }
+            CheatingStrategy::RandomGroupElements => Proof {
+                encrypted1_ratio_poly_at_s: E::encrypt1(rand::random()),
+                encrypted1_secret_poly_at_s: E::encrypt1(rand::random()),
+                encrypted1_alpha_times_secret_poly_at_s: E::encrypt1(rand::random()),
+            },
+            CheatingStrategy::ReusedProofOfAnotherTarget => {
+                let other_target = t * &Poly::new_from_coeffs(&[1, 1]);
+                let other_key = ProvingKey {
+                    target_polynomial: other_target.clone(),
+                    encrypted1_s_powers: key.encrypted1_s_powers.clone(),
+                    encrypted1_alpha_times_s_powers: key.encrypted1_alpha_times_s_powers.clone(),
+                };
+                Prover::new(&other_target * &Poly::new_from_coeffs(&[2])).prove(&other_key)?
+            }
+            CheatingStrategy::ScaledRatio => {
+                let p = &(t * &h) * &Poly::new_from_coeffs(&[2]) + Poly::new_from_coeffs(&[1]);
+                Proof {
+                    encrypted1_ratio_poly_at_s: E::homomorphic1_multiply(
+                        &evaluate(&key.encrypted1_s_powers, &h)?,
+                        2,
+                    ),
+                    encrypted1_secret_poly_at_s: evaluate(&key.encrypted1_s_powers, &p)?,
+                    encrypted1_alpha_times_secret_poly_at_s: evaluate(
+                        &key.encrypted1_alpha_times_s_powers,
+                        &p,
+                    )?,
+                }
+            }
+            CheatingStrategy::OmittedAlphaShift => {
+                let p = t * &h;
+                Proof {
+                    encrypted1_ratio_poly_at_s: evaluate(&key.encrypted1_s_powers, &h)?,
+                    encrypted1_secret_poly_at_s: evaluate(&key.encrypted1_s_powers, &p)?,
+                    encrypted1_alpha_times_secret_poly_at_s: evaluate(
+                        &key.encrypted1_s_powers,
+                        &p,
+                    )?,
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        crs_player::CrsPlayer,
+        verificiation::{failed_checks, VerificationCheck},
+        PublicData, VerifyingKey,
+    };
+
+    fn failed_checks_of(strategy: CheatingStrategy) -> Vec<VerificationCheck> {
+        let public = PublicData {
+            target_polynomial: Poly::new_from_coeffs(&[3, 4, 5]),
+            crs: CrsPlayer::new(12, 13).start_crs_ceremony().accumulator,
+        };
+        let proof = CheatingProver::new(strategy)
+            .prove(&ProvingKey::new(&public))
+            .expect("degree within the CRS");
+        return failed_checks(
+            &VerifyingKey::new(&public).expect("target degree within the CRS"),
+            &proof,
+        );
+    }
+
+    #[test]
+    fn polynomial_without_the_target_roots_fails_the_roots_check() {
+        assert_eq!(
+            failed_checks_of(CheatingStrategy::NotDivisibleByTarget),
+            vec![VerificationCheck::PolynomialHasTargetRoots]
+        );
+    }
+
+    #[test]
+    fn polynomial_beyond_the_crs_degree_fails_the_roots_check() {
+        assert_eq!(
+            failed_checks_of(CheatingStrategy::DegreeExceedingCrs),
+            vec![VerificationCheck::PolynomialHasTargetRoots]
+        );
+    }
+
+    #[test]
+    fn random_group_elements_fail_both_checks() {
+        assert_eq!(
+            failed_checks_of(CheatingStrategy::RandomGroupElements),
+            vec![
+                VerificationCheck::PolynomialHasTargetRoots,
+                VerificationCheck::PolynomialWasEvaluatedOnCrs
+            ]
+        );
+    }
+
+    #[test]
+    fn proof_of_another_target_fails_the_roots_check() {
+        assert_eq!(
+            failed_checks_of(CheatingStrategy::ReusedProofOfAnotherTarget),
+            vec![VerificationCheck::PolynomialHasTargetRoots]
+        );
+    }
+
+    #[test]
+    fn scaled_ratio_fails_the_roots_check() {
+        assert_eq!(
+            failed_checks_of(CheatingStrategy::ScaledRatio),
+            vec![VerificationCheck::PolynomialHasTargetRoots]
+        );
+    }
+
+    #[test]
+    fn missing_alpha_shift_fails_the_evaluation_check() {
+        assert_eq!(
+            failed_checks_of(CheatingStrategy::OmittedAlphaShift),
+            vec![VerificationCheck::PolynomialWasEvaluatedOnCrs]
+        );
+    }
+
+    #[test]
+    fn every_strategy_fails_a_check() {
+        for strategy in CheatingStrategy::ALL {
+            assert!(
+                !failed_checks_of(strategy).is_empty(),
+                "{:?} passes every check",
+                strategy
+            );
+        }
+    }
+}
@@ -0,0 +1,211 @@
+use zksnark::{field::FieldIdentity, groth16::FrLocal};
+
+use crate::qap::{Operation, QuadraticArithmeticProgram};
+
+/// the name of the variable that always holds the value 1, section 4.6 of the paper
+pub(crate) const ONE: &str = "one";
+
+pub(crate) enum Gate {
+    Add,
+    Multiply,
+}
+
+struct GateWiring {
+    gate: Gate,
+    left: usize,
+    right: usize,
+    output: usize,
+}
+
+/// An arithmetic circuit of two-input gates over named variables, built up one gate at a time.
+///
+/// Gates are evaluated in the order they were added, so every operand must be an input of the
+/// circuit or the output of an earlier gate.
+pub(crate) struct ArithmeticCircuit {
+    variable_names: Vec<String>,
+    gates: Vec<GateWiring>,
+}
+
+impl ArithmeticCircuit {
+    pub(crate) fn new() -> Self {
+        Self {
+            variable_names: vec![ONE.to_string()],
+            gates: vec![],
+        }
+    }
+
+    pub(crate) fn add(&mut self, left: &str, right: &str, output: &str) -> &mut Self {
+        return self.gate(Gate::Add, left, right, output);
+    }
+
+    pub(crate) fn multiply(&mut self, left: &str, right: &str, output: &str) -> &mut Self {
+        return self.gate(Gate::Multiply, left, right, output);
+    }
+
+    pub(crate) fn gate(&mut self, gate: Gate, left: &str, right: &str, output: &str) -> &mut Self {
+        let wiring = GateWiring {
+            gate,
+            left: self.variable_index_or_insert(left),
+            right: self.variable_index_or_insert(right),
+            output: self.variable_index_or_insert(output),
+        };
+        self.gates.push(wiring);
+        return self;
+    }
+
+    fn variable_index_or_insert(&mut self, name: &str) -> usize {
+        if let Some(index) = self.variable_index(name) {
+            return index;
+        }
+        self.variable_names.push(name.to_string());
+        return self.variable_names.len() - 1;
+    }
+
+    pub(crate) fn variable_index(&self, name: &str) -> Option<usize> {
+        return self.variable_names.iter().position(|n| n == name);
+    }
+
+    pub(crate) fn number_of_variables(&self) -> usize {
+        return self.variable_names.len();
+    }
+
+    /// Each gate becomes one operation: `a * b = c` as it is, and `a + b = c` as `(a + b) * 1 = c`.
+    pub(crate) fn to_operations(&self) -> Vec<Operation> {
+        let one = FrLocal::one();
+        return self
+            .gates
+            .iter()
+            .map(|wiring| match wiring.gate {
+                Gate::Add => Operation {
+                    left: vec![(wiring.left, one), (wiring.right, one)],
+                    right: vec![(0, one)],
+                    output: vec![(wiring.output, one)],
+                },
+                Gate::Multiply => Operation {
+                    left: vec![(wiring.left, one)],
+                    right: vec![(wiring.right, one)],
+                    output: vec![(wiring.output, one)],
+                },
+            })
+            .collect();
+    }
+
+    pub(crate) fn compile(&self) -> QuadraticArithmeticProgram {
+        return QuadraticArithmeticProgram::new(self.number_of_variables(), &self.to_operations());
+    }
+
+    /// Evaluates the gates on the given inputs to get a value for every variable, indexed like the QAP's
+    /// variable polynomials.
+    pub(crate) fn compute_witness(
+        &self,
+        inputs: &[(&str, usize)],
+    ) -> Result<Vec<FrLocal>, WitnessError> {
+        let mut values: Vec<Option<FrLocal>> = vec![None; self.number_of_variables()];
+        values[0] = Some(FrLocal::one());
+        for (name, value) in inputs {
+            let index = self
+                .variable_index(name)
+                .ok_or_else(|| WitnessError::UnknownInput {
+                    name: name.to_string(),
+                })?;
+            values[index] = Some(FrLocal::from(*value));
+        }
+
+        let value_of =
+            |values: &[Option<FrLocal>], index: usize| -> Result<FrLocal, WitnessError> {
+                return values[index].ok_or_else(|| WitnessError::MissingInput {
+                    name: self.variable_names[index].clone(),
+                });
+            };
+        for wiring in &self.gates {
+            let left = value_of(&values, wiring.left)?;
+            let right = value_of(&values, wiring.right)?;
+            values[wiring.output] = Some(match wiring.gate {
+                Gate::Add => left + right,
+                Gate::Multiply => left * right,
+            });
+        }
+
+        return (0..values.len())
+            .map(|index| value_of(&values, index))
+            .collect();
+    }
+}
+
+/// Why `ArithmeticCircuit::compute_witness` could not evaluate the circuit
+#[derive(Debug, PartialEq)]
+pub(crate) enum WitnessError {
+    /// no variable of the circuit has this name
+    UnknownInput { name: String },
+    /// the variable is an operand of a gate but neither an input nor the output of an earlier gate
+    MissingInput { name: String },
+}
+
+/// `out = x^3 + x`, the circuit of the demos and tests
+pub(crate) fn cubic_circuit() -> ArithmeticCircuit {
+    let mut circuit = ArithmeticCircuit::new();
+    circuit
+        .multiply("x", "x", "x_squared")
+        .multiply("x_squared", "x", "x_cubed")
+        .add("x_cubed", "x", "out");
+    return circuit;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::prove_and_verify;
+
+    #[test]
+    fn witness_holds_every_gate_output() {
+        let circuit = cubic_circuit();
+        let witness = circuit
+            .compute_witness(&[("x", 3)])
+            .expect("x is the circuit's input");
+
+        let value_of = |name: &str| witness[circuit.variable_index(name).expect("variable")];
+        assert!(value_of(ONE) == FrLocal::from(1));
+        assert!(value_of("x_squared") == FrLocal::from(9));
+        assert!(value_of("x_cubed") == FrLocal::from(27));
+        assert!(value_of("out") == FrLocal::from(30));
+    }
+
+    #[test]
+    fn satisfying_witness_proves_the_circuit() {
+        let circuit = cubic_circuit();
+        let witness = circuit
+            .compute_witness(&[("x", 3)])
+            .expect("x is the circuit's input");
+
+        assert!(prove_and_verify(&circuit.compile(), &witness));
+    }
+
+    #[test]
+    fn tampered_witness_does_not_prove_the_circuit() {
+        let circuit = cubic_circuit();
+        let mut witness = circuit
+            .compute_witness(&[("x", 3)])
+            .expect("x is the circuit's input");
+        witness[circuit.variable_index("x_cubed").expect("variable")] = FrLocal::from(28);
+
+        assert!(!prove_and_verify(&circuit.compile(), &witness));
+    }
+
+    #[test]
+    fn witness_refuses_unknown_and_missing_inputs() {
+        let circuit = cubic_circuit();
+
+        assert_eq!(
+            circuit.compute_witness(&[("x", 3), ("y", 4)]).err(),
+            Some(WitnessError::UnknownInput {
+                name: "y".to_string()
+            })
+        );
+        assert_eq!(
+            circuit.compute_witness(&[]).err(),
+            Some(WitnessError::MissingInput {
+                name: "x".to_string()
+            })
+        );
+    }
+}
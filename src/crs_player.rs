@@ -1,57 +1,76 @@
-use zksnark::groth16::fr::{G1Local, G2Local};
+use std::marker::PhantomData;
+
+use zksnark::{
+    field::{powers, FieldIdentity, Polynomial},
+    groth16::{
+        coefficient_poly::CoefficientPoly,
+        fr::{G1Local, G2Local},
+        FrLocal,
+    },
+};
 
 use crate::{
     cryptography::{
-        crypto_pairing, encrypt1, encrypt2, homomorphic1_multiply, homomorphic2_multiply,
-        mult_and_encrypt1, mult_and_encrypt2,
+        encrypt1_field_element, encrypt2_field_element, encrypted1_one, encrypted2_one,
     },
-    CrsCeremonyStep, CrsCeremonyTranscript, CrsCeremonyValues, CRS, POLYNOMIAL_DEGREE,
+    pairing_engine::{BnEngine, EncodableEngine, PairingEngine, ZksnarkEngine},
+    qap::QuadraticArithmeticProgram,
+    trace,
+    transcript::Transcript,
+    ComputationProvingKey, ComputationVerifyingKey, CrsCeremonyStep, CrsCeremonyTranscript,
+    CrsCeremonyValues, CRS, POLYNOMIAL_DEGREE,
 };
 
-pub(crate) struct CrsPlayer {
+/// The secrets a computation setup needs on top of `s`. The output operand's rho is `rho_l * rho_r`.
+pub(crate) struct ComputationSecrets {
+    pub(crate) alpha_l: usize,
+    pub(crate) alpha_r: usize,
+    pub(crate) alpha_o: usize,
+    pub(crate) beta: usize,
+    pub(crate) gamma: usize,
+    pub(crate) rho_l: usize,
+    pub(crate) rho_r: usize,
+}
+
+/// `E` is the pairing engine the ceremony's group elements belong to
+pub(crate) struct CrsPlayer<E: PairingEngine = BnEngine> {
     s: usize,     // secret value at which the polynomial is to be evaluated
     alpha: usize, // value for scaling the polynomial as part proof/validation algorithm for restricting how the prover generates the proof
+    engine: PhantomData<E>,
 }
 
 impl CrsPlayer {
     pub fn new(s: usize, alpha: usize) -> Self {
-        Self { s, alpha }
+        return Self::with_engine(s, alpha);
     }
+}
 
-    fn get_crs_step_values(&self) -> CrsCeremonyStep {
-        let step_encrypted1_s_powers: Vec<G1Local> = (0..POLYNOMIAL_DEGREE + 1)
-            .map(|k| self.s.checked_pow(k).expect("not to overflow"))
-            .map(|s_to_kth_power: usize| encrypt1(s_to_kth_power))
-            .collect();
-
-        let step_encrypted2_alpha_times_s_powers: Vec<G2Local> = (0..POLYNOMIAL_DEGREE + 1)
-            .map(|k| self.s.checked_pow(k).expect("not to overflow"))
-            .map(|s_to_kth_power: usize| mult_and_encrypt2(s_to_kth_power, self.alpha))
-            .collect();
-
-        return CrsCeremonyStep {
-            encrypted1_s_powers: step_encrypted1_s_powers,
-            encrypted1_alpha: encrypt1(self.alpha),
-            encrypted2_alpha_times_s_powers: step_encrypted2_alpha_times_s_powers,
-        };
+impl<E: PairingEngine> CrsPlayer<E> {
+    /// a player on another backend than the default, as in `CrsPlayer::<ZksnarkEngine>::with_engine`
+    pub(crate) fn with_engine(s: usize, alpha: usize) -> Self {
+        Self {
+            s,
+            alpha,
+            engine: PhantomData,
+        }
     }
 
-    pub(crate) fn start_crs_ceremony(&self) -> CrsCeremonyValues {
-        let encrypted2_alpha = encrypt2(self.alpha);
+    pub(crate) fn start_crs_ceremony(&self) -> CrsCeremonyValues<E> {
+        let encrypted2_alpha = E::encrypt2(self.alpha);
 
-        let encrypted2_s_powers: Vec<G2Local> = (0..POLYNOMIAL_DEGREE + 1)
+        let encrypted2_s_powers: Vec<E::G2> = (0..POLYNOMIAL_DEGREE + 1)
             .map(|k| self.s.checked_pow(k).expect("not to overflow"))
-            .map(|s_to_kth_power: usize| encrypt2(s_to_kth_power))
+            .map(|s_to_kth_power: usize| E::encrypt2(s_to_kth_power))
             .collect();
 
-        let encrypted1_s_powers: Vec<G1Local> = (0..POLYNOMIAL_DEGREE + 1)
+        let encrypted1_s_powers: Vec<E::G1> = (0..POLYNOMIAL_DEGREE + 1)
             .map(|k| self.s.checked_pow(k).expect("not to overflow"))
-            .map(|s_to_kth_power: usize| encrypt1(s_to_kth_power))
+            .map(|s_to_kth_power: usize| E::encrypt1(s_to_kth_power))
             .collect();
 
-        let encrypted1_alpha_times_s_powers: Vec<G1Local> = (0..POLYNOMIAL_DEGREE + 1)
+        let encrypted1_alpha_times_s_powers: Vec<E::G1> = (0..POLYNOMIAL_DEGREE + 1)
             .map(|k| self.s.checked_pow(k).expect("not to overflow"))
-            .map(|s_to_kth_power: usize| mult_and_encrypt1(s_to_kth_power, self.alpha))
+            .map(|s_to_kth_power: usize| E::mult_and_encrypt1(s_to_kth_power, self.alpha))
             .collect();
 
         let retval = CrsCeremonyValues {
@@ -61,179 +80,640 @@ impl CrsPlayer {
                 encrypted1_s_powers,
                 encrypted1_alpha_times_s_powers,
             },
-            step: self.get_crs_step_values(),
+            step: crs_step_values::<E>(
+                &scalar_powers::<E>(E::scalar(self.s), POLYNOMIAL_DEGREE as usize + 1),
+                E::scalar(self.alpha),
+            ),
         };
+        trace::note(
+            "3.6.3",
+            "Ceremony: first contribution",
+            "The first player encrypts the powers of its secret s up to the maximum degree, in both \
+             groups, and their copies shifted by its secret alpha.",
+            || describe_crs(&retval.accumulator),
+        );
 
         self.secure_erase_my_secret_s_and_alpha_values();
 
         return retval;
     }
 
-    ///
+    /// Checks the transcript before adding this player's secrets to its last accumulator
     pub(crate) fn continue_crs_ceremony(
         &self,
-        transcript: &CrsCeremonyTranscript,
-    ) -> CrsCeremonyValues {
-        validate_ceremony_transcript(transcript);
+        transcript: &CrsCeremonyTranscript<E>,
+    ) -> Result<CrsCeremonyValues<E>, CeremonyError> {
+        return self.continue_crs_ceremony_of_degree(transcript, POLYNOMIAL_DEGREE as usize);
+    }
+
+    /// `continue_crs_ceremony` for a ceremony of another degree than `POLYNOMIAL_DEGREE`, such as
+    /// one cut down by `truncate_ceremony_transcript`
+    pub(crate) fn continue_crs_ceremony_of_degree(
+        &self,
+        transcript: &CrsCeremonyTranscript<E>,
+        degree: usize,
+    ) -> Result<CrsCeremonyValues<E>, CeremonyError> {
+        validate_ceremony_transcript_of_degree(transcript, degree)?;
+        trace::note(
+            "3.6.3",
+            "Ceremony: transcript check",
+            "Before contributing, the player checks with pairings that every CRS so far is consistent \
+             and that every step used the values it states.",
+            || vec![("contributions checked", transcript.history.len().to_string())],
+        );
+
         let retval = self.mix_my_secrets_into_ceremony_transcript(transcript);
+        trace::note(
+            "3.6.3",
+            "Ceremony: next contribution",
+            "The player multiplies each encrypted power of s by the same power of its own s, and the \
+             shifted ones by its alpha too, so nobody knows the combined secrets.",
+            || describe_crs(&retval.accumulator),
+        );
 
         self.secure_erase_my_secret_s_and_alpha_values();
 
-        return retval;
+        return Ok(retval);
     }
 
     fn mix_my_secrets_into_ceremony_transcript(
         &self,
-        transcript: &CrsCeremonyTranscript,
-    ) -> CrsCeremonyValues {
+        transcript: &CrsCeremonyTranscript<E>,
+    ) -> CrsCeremonyValues<E> {
         let crs_current = &transcript.history.last().expect("non-empty").accumulator;
+        return mix_secrets_into_crs(crs_current, E::scalar(self.s), E::scalar(self.alpha));
+    }
 
-        let new_encrypted2_alpha = homomorphic2_multiply(&crs_current.encrypted2_alpha, self.alpha);
+    fn secure_erase_my_secret_s_and_alpha_values(&self) {
+        // this function is just for show, it doesn't really apply to this project as it is at the moment
+    }
+}
 
-        let new_encrypted2_s_powers: Vec<G2Local> = (0..POLYNOMIAL_DEGREE + 1)
-            .map(|k| self.s.checked_pow(k).expect("not to overflow"))
-            .zip(&crs_current.encrypted2_s_powers)
-            .map(
-                |(s_to_kth_power, ceremony_current_encrypted2_s_to_kth_power)| {
-                    homomorphic2_multiply(
-                        &ceremony_current_encrypted2_s_to_kth_power,
-                        s_to_kth_power,
-                    )
-                },
-            )
+impl CrsPlayer<ZksnarkEngine> {
+    /// Creates the keys for proving computations of one QAP, sections 4.4 to 4.9 of the paper, on the
+    /// zksnark crate's types the QAP is written in.
+    ///
+    /// The keys hold sums of different secrets, such as `beta * (rho_l l_i(s) + rho_r r_i(s) + ...)`,
+    /// which later players cannot update multiplicatively, so this setup is done by a single player.
+    ///
+    /// `public_inputs` are the indices of the variables whose values the verifier supplies, section
+    /// 4.9. The constant one (variable 0) is always public and is not listed.
+    pub(crate) fn create_computation_keys(
+        &self,
+        qap: &QuadraticArithmeticProgram,
+        public_inputs: &[usize],
+        secrets: &ComputationSecrets,
+    ) -> Result<(ComputationProvingKey, ComputationVerifyingKey), PublicInputError> {
+        validate_public_inputs(qap, public_inputs)?;
+        let public_variables: Vec<usize> = std::iter::once(0)
+            .chain(public_inputs.iter().copied())
+            .collect();
+        let private_variables: Vec<usize> = (0..qap.number_of_variables())
+            .filter(|variable| !public_variables.contains(variable))
             .collect();
 
-        let new_encrypted1_s_powers: Vec<G1Local> = (0..POLYNOMIAL_DEGREE + 1)
-            .map(|k| self.s.checked_pow(k).expect("not to overflow"))
-            .zip(&crs_current.encrypted1_s_powers)
-            .map(
-                |(s_to_kth_power, ceremony_current_encrypted1_s_to_kth_power)| {
-                    homomorphic1_multiply(
-                        &ceremony_current_encrypted1_s_to_kth_power,
-                        s_to_kth_power,
-                    )
-                },
-            )
+        let s = FrLocal::from(self.s);
+        let alpha_l = FrLocal::from(secrets.alpha_l);
+        let alpha_r = FrLocal::from(secrets.alpha_r);
+        let alpha_o = FrLocal::from(secrets.alpha_o);
+        let beta = FrLocal::from(secrets.beta);
+        let gamma = FrLocal::from(secrets.gamma);
+        let rho_l = FrLocal::from(secrets.rho_l);
+        let rho_r = FrLocal::from(secrets.rho_r);
+        let rho_o = rho_l * rho_r;
+
+        let at_s = |polynomials: &[CoefficientPoly<FrLocal>], scale: FrLocal| -> Vec<FrLocal> {
+            return polynomials
+                .iter()
+                .map(|polynomial| polynomial.evaluate(s) * scale)
+                .collect();
+        };
+        let left_at_s = at_s(qap.left_variable_polynomials(), rho_l);
+        let right_at_s = at_s(qap.right_variable_polynomials(), rho_r);
+        let output_at_s = at_s(qap.output_variable_polynomials(), rho_o);
+
+        let encrypted1 =
+            |values: &[FrLocal], variables: &[usize], scale: FrLocal| -> Vec<G1Local> {
+                return variables
+                    .iter()
+                    .map(|variable| encrypt1_field_element(values[*variable] * scale))
+                    .collect();
+            };
+        let encrypted2 =
+            |values: &[FrLocal], variables: &[usize], scale: FrLocal| -> Vec<G2Local> {
+                return variables
+                    .iter()
+                    .map(|variable| encrypt2_field_element(values[*variable] * scale))
+                    .collect();
+            };
+
+        let variable_consistency_at_s: Vec<FrLocal> = (0..qap.number_of_variables())
+            .map(|i| left_at_s[i] + right_at_s[i] + output_at_s[i])
             .collect();
 
-        let new_encrypted1_alpha_times_s_powers: Vec<G1Local> = (0..POLYNOMIAL_DEGREE + 1)
-            .map(|k| self.s.checked_pow(k).expect("not to overflow"))
-            .zip(&crs_current.encrypted1_alpha_times_s_powers)
-            .map(
-                |(s_to_kth_power, ceremony_current_encrypted1_alpha_times_s_power)| {
-                    homomorphic1_multiply(
-                        &ceremony_current_encrypted1_alpha_times_s_power,
-                        self.alpha * s_to_kth_power,
-                    )
-                },
-            )
+        // h(x) has degree at most n - 2 for n operations
+        let encrypted1_s_powers: Vec<G1Local> = powers(s)
+            .take(qap.number_of_operations())
+            .map(encrypt1_field_element)
             .collect();
 
-        return CrsCeremonyValues {
-            accumulator: CRS {
-                encrypted2_alpha: new_encrypted2_alpha,
-                encrypted2_s_powers: new_encrypted2_s_powers,
-                encrypted1_s_powers: new_encrypted1_s_powers,
-                encrypted1_alpha_times_s_powers: new_encrypted1_alpha_times_s_powers,
-            },
-            step: self.get_crs_step_values(),
+        let proving_key = ComputationProvingKey {
+            target_polynomial: qap.target_polynomial().clone(),
+            encrypted1_s_powers,
+            encrypted1_left_variable_polys_at_s: encrypted1(
+                &left_at_s,
+                &private_variables,
+                FrLocal::one(),
+            ),
+            encrypted2_right_variable_polys_at_s: encrypted2(
+                &right_at_s,
+                &private_variables,
+                FrLocal::one(),
+            ),
+            encrypted1_output_variable_polys_at_s: encrypted1(
+                &output_at_s,
+                &private_variables,
+                FrLocal::one(),
+            ),
+            encrypted1_alpha_times_left_variable_polys_at_s: encrypted1(
+                &left_at_s,
+                &private_variables,
+                alpha_l,
+            ),
+            encrypted2_alpha_times_right_variable_polys_at_s: encrypted2(
+                &right_at_s,
+                &private_variables,
+                alpha_r,
+            ),
+            encrypted1_alpha_times_output_variable_polys_at_s: encrypted1(
+                &output_at_s,
+                &private_variables,
+                alpha_o,
+            ),
+            encrypted1_variable_consistency_polys_at_s: encrypted1(
+                &variable_consistency_at_s,
+                &private_variables,
+                beta,
+            ),
+            private_variables,
         };
+
+        let verifying_key = ComputationVerifyingKey {
+            encrypted1_one: encrypted1_one(),
+            encrypted2_one: encrypted2_one(),
+            encrypted2_alpha_left: encrypt2_field_element(alpha_l),
+            encrypted1_alpha_right: encrypt1_field_element(alpha_r),
+            encrypted2_alpha_output: encrypt2_field_element(alpha_o),
+            encrypted1_beta_times_gamma: encrypt1_field_element(beta * gamma),
+            encrypted2_beta_times_gamma: encrypt2_field_element(beta * gamma),
+            encrypted2_gamma: encrypt2_field_element(gamma),
+            encrypted2_target_poly_at_s: encrypt2_field_element(
+                qap.target_polynomial().evaluate(s) * rho_o,
+            ),
+            encrypted1_left_public_variable_polys_at_s: encrypted1(
+                &left_at_s,
+                &public_variables,
+                FrLocal::one(),
+            ),
+            encrypted2_right_public_variable_polys_at_s: encrypted2(
+                &right_at_s,
+                &public_variables,
+                FrLocal::one(),
+            ),
+            encrypted1_output_public_variable_polys_at_s: encrypted1(
+                &output_at_s,
+                &public_variables,
+                FrLocal::one(),
+            ),
+        };
+
+        self.secure_erase_my_secret_s_and_alpha_values();
+
+        return Ok((proving_key, verifying_key));
     }
+}
 
-    fn secure_erase_my_secret_s_and_alpha_values(&self) {
-        // this function is just for show, it doesn't really apply to this project as it is at the moment
+/// Why `create_computation_keys` or `Groth16Key::new` refused its list of public inputs
+#[derive(Debug, PartialEq)]
+pub(crate) enum PublicInputError {
+    /// the constant one is always public, listing it would count it twice
+    ConstantOneListed,
+    /// the QAP has no variable of this index
+    VariableOutOfRange {
+        variable: usize,
+        number_of_variables: usize,
+    },
+    /// the variable is listed more than once
+    DuplicateVariable { variable: usize },
+}
+
+/// shared with `Groth16Key::new`, which takes the same list
+pub(crate) fn validate_public_inputs(
+    qap: &QuadraticArithmeticProgram,
+    public_inputs: &[usize],
+) -> Result<(), PublicInputError> {
+    let number_of_variables = qap.number_of_variables();
+    for (index, variable) in public_inputs.iter().enumerate() {
+        if *variable == 0 {
+            return Err(PublicInputError::ConstantOneListed);
+        }
+        if *variable >= number_of_variables {
+            return Err(PublicInputError::VariableOutOfRange {
+                variable: *variable,
+                number_of_variables,
+            });
+        }
+        if public_inputs[..index].contains(variable) {
+            return Err(PublicInputError::DuplicateVariable {
+                variable: *variable,
+            });
+        }
     }
+    return Ok(());
 }
 
-fn validate_ceremony_transcript(transcript: &CrsCeremonyTranscript) -> () {
-    let all_internally_consistent = transcript
-        .history
+/// s^0 to s^(number_of_powers - 1), taken in the scalar field so no power overflows
+fn scalar_powers<E: PairingEngine>(s: E::Scalar, number_of_powers: usize) -> Vec<E::Scalar> {
+    return std::iter::successors(Some(E::scalar(1)), |power| Some(*power * s))
+        .take(number_of_powers)
+        .collect();
+}
+
+/// the values a contribution states, so the next players can check its update
+fn crs_step_values<E: PairingEngine>(
+    s_powers: &[E::Scalar],
+    alpha: E::Scalar,
+) -> CrsCeremonyStep<E> {
+    return CrsCeremonyStep {
+        encrypted1_s_powers: s_powers
+            .iter()
+            .map(|s_to_kth_power| E::multiply1(E::generator1(), *s_to_kth_power))
+            .collect(),
+        encrypted1_alpha: E::multiply1(E::generator1(), alpha),
+        encrypted2_alpha_times_s_powers: s_powers
+            .iter()
+            .map(|s_to_kth_power| E::multiply2(E::generator2(), alpha * *s_to_kth_power))
+            .collect(),
+    };
+}
+
+/// `crs` with the secrets `s` and `alpha` mixed in, as many powers as it holds, which need not be
+/// `POLYNOMIAL_DEGREE + 1`
+fn mix_secrets_into_crs<E: PairingEngine>(
+    crs: &CRS<E>,
+    s: E::Scalar,
+    alpha: E::Scalar,
+) -> CrsCeremonyValues<E> {
+    let s_powers = scalar_powers::<E>(s, crs.encrypted1_s_powers.len());
+
+    let new_encrypted2_s_powers: Vec<E::G2> = crs
+        .encrypted2_s_powers
         .iter()
-        .all(|ccv| current_crs_value_is_internally_consistent(&ccv.accumulator));
+        .zip(&s_powers)
+        .map(|(encrypted2_s_power, s_to_kth_power)| {
+            E::multiply2(*encrypted2_s_power, *s_to_kth_power)
+        })
+        .collect();
 
-    let every_ccv_includes_entropy_from_previous_ccv = (1..transcript.history.len()).all(|index| {
-        is_valid_crs_ceremony_step(&transcript.history[index - 1], &transcript.history[index])
-    });
+    let new_encrypted1_s_powers: Vec<E::G1> = crs
+        .encrypted1_s_powers
+        .iter()
+        .zip(&s_powers)
+        .map(|(encrypted1_s_power, s_to_kth_power)| {
+            E::multiply1(*encrypted1_s_power, *s_to_kth_power)
+        })
+        .collect();
+
+    let new_encrypted1_alpha_times_s_powers: Vec<E::G1> = crs
+        .encrypted1_alpha_times_s_powers
+        .iter()
+        .zip(&s_powers)
+        .map(|(encrypted1_alpha_times_s_power, s_to_kth_power)| {
+            E::multiply1(*encrypted1_alpha_times_s_power, alpha * *s_to_kth_power)
+        })
+        .collect();
+
+    return CrsCeremonyValues {
+        accumulator: CRS {
+            encrypted2_alpha: E::multiply2(crs.encrypted2_alpha, alpha),
+            encrypted2_s_powers: new_encrypted2_s_powers,
+            encrypted1_s_powers: new_encrypted1_s_powers,
+            encrypted1_alpha_times_s_powers: new_encrypted1_alpha_times_s_powers,
+        },
+        step: crs_step_values::<E>(&s_powers, alpha),
+    };
+}
+
+const BEACON_LABEL: &str = "learning-zksnarks ceremony beacon";
+
+/// The s and alpha of the contribution that finalizes a ceremony, hashed from the beacon so anyone
+/// can derive them
+fn beacon_secrets<E: EncodableEngine>(beacon: &[u8]) -> (E::Scalar, E::Scalar) {
+    let mut transcript = Transcript::<E>::new(BEACON_LABEL);
+    transcript.append_bytes("beacon", beacon);
+    let s = transcript.challenge_field_element("s");
+    let alpha = transcript.challenge_field_element("alpha");
+    return (s, alpha);
+}
+
+/// Checks the transcript, then makes the contribution that finalizes it with a public beacon, such
+/// as the hash of a block mined after the last player contributed.
+///
+/// Its secrets are hashed from the beacon, which no player knew while contributing, so no player
+/// could pick its own secrets to steer the final CRS. They are public, so this contribution adds no
+/// secrecy: the final CRS is trapdoor-free as long as one player erased its secrets.
+pub(crate) fn finalize_ceremony_with_beacon<E: EncodableEngine>(
+    transcript: &CrsCeremonyTranscript<E>,
+    beacon: &[u8],
+) -> Result<CrsCeremonyValues<E>, CeremonyError> {
+    validate_ceremony_transcript(transcript)?;
+    let crs_current = &transcript.history.last().expect("non-empty").accumulator;
+    let (s, alpha) = beacon_secrets::<E>(beacon);
+    let retval = mix_secrets_into_crs(crs_current, s, alpha);
+    trace::note(
+        "3.6.3",
+        "Ceremony: beacon contribution",
+        "A last contribution whose s and alpha are hashed from a public beacon announced after \
+         every player contributed, so anyone can recompute it.",
+        || describe_crs(&retval.accumulator),
+    );
+    return Ok(retval);
+}
+
+/// Validates a ceremony whose last contribution must be the one `finalize_ceremony_with_beacon`
+/// makes from `beacon`, which is recomputed from the accumulator before it
+pub(crate) fn validate_ceremony_finalized_by_beacon<E: EncodableEngine>(
+    transcript: &CrsCeremonyTranscript<E>,
+    beacon: &[u8],
+) -> Result<(), CeremonyError> {
+    validate_ceremony_transcript(transcript)?;
+    let [.., previous, last] = &transcript.history[..] else {
+        return Err(CeremonyError::BeaconContributionMismatch);
+    };
+    let (s, alpha) = beacon_secrets::<E>(beacon);
+    let expected = mix_secrets_into_crs(&previous.accumulator, s, alpha);
+    if !same_crs(&expected.accumulator, &last.accumulator) {
+        return Err(CeremonyError::BeaconContributionMismatch);
+    }
+    return Ok(());
+}
 
-    if !(all_internally_consistent && every_ccv_includes_entropy_from_previous_ccv) {
-        panic!("transcript not valid") // TOOD use Result instead
+fn describe_crs<E: PairingEngine>(crs: &CRS<E>) -> Vec<(&'static str, String)> {
+    let describe_all = |elements: &[E::G1]| -> String {
+        return elements
+            .iter()
+            .map(E::describe1)
+            .collect::<Vec<String>>()
+            .join(", ");
     };
+    return vec![
+        ("g^(s^k)", describe_all(&crs.encrypted1_s_powers)),
+        (
+            "g^(alpha s^k)",
+            describe_all(&crs.encrypted1_alpha_times_s_powers),
+        ),
+        ("g^alpha", E::describe2(&crs.encrypted2_alpha)),
+    ];
+}
+
+/// Why `validate_ceremony_transcript` rejected a transcript, with the index in its history of the
+/// first contribution at fault
+#[derive(Debug, PartialEq)]
+pub(crate) enum CeremonyError {
+    /// the history holds no contribution, so there is no CRS to continue or to use
+    EmptyTranscript,
+    /// a vector of the accumulator or of the step does not hold one element per power of s
+    WrongNumberOfPowers { contribution: usize },
+    /// the alpha-shifted powers are not alpha times the powers
+    AlphaShiftMismatch { contribution: usize },
+    /// the powers in G1 and in G2 encrypt different values
+    G1AndG2PowersMismatch { contribution: usize },
+    /// the powers are not the successive powers of a single s
+    PowersAreNotGeometric { contribution: usize },
+    /// the accumulator is one an earlier contribution already published
+    ReplayedAccumulator { contribution: usize },
+    /// the step's s or alpha is 0, which would erase every earlier contribution, or 1, which adds no
+    /// entropy
+    DegenerateSecret { contribution: usize },
+    /// the accumulator holds the encryption of 0, which no later contribution can change
+    PointAtInfinity { contribution: usize },
+    /// the accumulator is not the previous one updated with the values the step states
+    StepDoesNotMatchUpdate { contribution: usize },
+    /// the last contribution is not the one the beacon's secrets make from the accumulator before
+    /// it, or no contribution comes before it
+    BeaconContributionMismatch,
 }
 
-fn current_crs_value_is_internally_consistent(accumulator: &CRS) -> bool {
-    // the validation code only works if the lengths are correct so validate that first
-    let correct_size = (POLYNOMIAL_DEGREE + 1) as usize;
-    if !(accumulator.encrypted2_s_powers.len() == correct_size
-        && accumulator.encrypted1_s_powers.len() == correct_size
-        && accumulator.encrypted1_alpha_times_s_powers.len() == correct_size)
-    {
-        return false; // TODO ideally refactor to a pattern that returns more info, such as Result
+/// Validates a ceremony run for `POLYNOMIAL_DEGREE`
+pub(crate) fn validate_ceremony_transcript<E: PairingEngine>(
+    transcript: &CrsCeremonyTranscript<E>,
+) -> Result<(), CeremonyError> {
+    return validate_ceremony_transcript_of_degree(transcript, POLYNOMIAL_DEGREE as usize);
+}
+
+/// Validates a ceremony whose every vector must hold the powers of s up to `degree`, which is below
+/// `POLYNOMIAL_DEGREE` only for a transcript cut down by `truncate_ceremony_transcript`
+pub(crate) fn validate_ceremony_transcript_of_degree<E: PairingEngine>(
+    transcript: &CrsCeremonyTranscript<E>,
+    degree: usize,
+) -> Result<(), CeremonyError> {
+    let number_of_powers = degree + 1;
+    if transcript.history.is_empty() {
+        return Err(CeremonyError::EmptyTranscript);
+    }
+    for (contribution, ccv) in transcript.history.iter().enumerate() {
+        // the other checks index the vectors, so their lengths are validated first
+        if !has_one_element_per_power(ccv, number_of_powers) {
+            return Err(CeremonyError::WrongNumberOfPowers { contribution });
+        }
+        if has_degenerate_secret(&ccv.step) {
+            return Err(CeremonyError::DegenerateSecret { contribution });
+        }
+        if contains_point_at_infinity(&ccv.accumulator) {
+            return Err(CeremonyError::PointAtInfinity { contribution });
+        }
+        current_crs_value_is_internally_consistent(&ccv.accumulator, contribution)?;
+
+        if contribution == 0 {
+            continue;
+        }
+        if transcript.history[..contribution]
+            .iter()
+            .any(|earlier| same_crs(&earlier.accumulator, &ccv.accumulator))
+        {
+            return Err(CeremonyError::ReplayedAccumulator { contribution });
+        }
+        if !is_valid_crs_ceremony_step(&transcript.history[contribution - 1], ccv) {
+            return Err(CeremonyError::StepDoesNotMatchUpdate { contribution });
+        }
     }
+    return Ok(());
+}
+
+/// at least s^0 and s^1, which every check relies on
+fn has_one_element_per_power<E: PairingEngine>(
+    ccv: &CrsCeremonyValues<E>,
+    correct_size: usize,
+) -> bool {
+    return correct_size >= 2
+        && ccv.accumulator.encrypted2_s_powers.len() == correct_size
+        && ccv.accumulator.encrypted1_s_powers.len() == correct_size
+        && ccv.accumulator.encrypted1_alpha_times_s_powers.len() == correct_size
+        && ccv.step.encrypted1_s_powers.len() == correct_size
+        && ccv.step.encrypted2_alpha_times_s_powers.len() == correct_size;
+}
+
+/// whether the step states an s or alpha of 0 or 1
+fn has_degenerate_secret<E: PairingEngine>(step: &CrsCeremonyStep<E>) -> bool {
+    let degenerate_values = [E::encrypt1(0), E::encrypt1(1)];
+    return degenerate_values.contains(&step.encrypted1_s_powers[1])
+        || degenerate_values.contains(&step.encrypted1_alpha);
+}
 
+fn contains_point_at_infinity<E: PairingEngine>(crs: &CRS<E>) -> bool {
+    let infinity1 = E::encrypt1(0);
+    let infinity2 = E::encrypt2(0);
+    return crs.encrypted2_alpha == infinity2
+        || crs.encrypted2_s_powers.contains(&infinity2)
+        || crs.encrypted1_s_powers.contains(&infinity1)
+        || crs.encrypted1_alpha_times_s_powers.contains(&infinity1);
+}
+
+fn same_crs<E: PairingEngine>(a: &CRS<E>, b: &CRS<E>) -> bool {
+    return a.encrypted2_alpha == b.encrypted2_alpha
+        && a.encrypted2_s_powers == b.encrypted2_s_powers
+        && a.encrypted1_s_powers == b.encrypted1_s_powers
+        && a.encrypted1_alpha_times_s_powers == b.encrypted1_alpha_times_s_powers;
+}
+
+/// expects the same number of powers of s in every vector, of whatever degree, see
+/// `has_one_element_per_power`
+fn current_crs_value_is_internally_consistent<E: PairingEngine>(
+    accumulator: &CRS<E>,
+    contribution: usize,
+) -> Result<(), CeremonyError> {
     let valid_alpha_relation = accumulator
         .encrypted1_s_powers
         .iter()
         .zip(&accumulator.encrypted1_alpha_times_s_powers)
         .all(|(e1_s_k, e1_a_s_k)| {
-            crypto_pairing(*e1_s_k, accumulator.encrypted2_alpha)
-                == crypto_pairing(*e1_a_s_k, encrypt2(1))
+            E::pairing_product_is_one(&[
+                (*e1_s_k, accumulator.encrypted2_alpha),
+                (E::negate1(*e1_a_s_k), E::generator2()),
+            ])
         });
+    if !valid_alpha_relation {
+        return Err(CeremonyError::AlphaShiftMismatch { contribution });
+    }
 
     let both_encryptions_of_s_powers_match = accumulator
         .encrypted1_s_powers
         .iter()
         .zip(&accumulator.encrypted2_s_powers)
         .all(|(encrypted1_s_power, encrypted2_s_power)| {
-            crypto_pairing(*encrypted1_s_power, encrypt2(1))
-                == crypto_pairing(encrypt1(1), *encrypted2_s_power)
+            E::pairing_product_is_one(&[
+                (*encrypted1_s_power, E::generator2()),
+                (E::negate1(E::generator1()), *encrypted2_s_power),
+            ])
         });
+    if !both_encryptions_of_s_powers_match {
+        return Err(CeremonyError::G1AndG2PowersMismatch { contribution });
+    }
 
     let both_vecs_of_s_powers_have_same_ratios =
-        (1..(POLYNOMIAL_DEGREE + 1) as usize).all(|index1| {
-            let expected_pairing = crypto_pairing(
-                accumulator.encrypted1_s_powers[0],
-                accumulator.encrypted2_s_powers[index1],
-            );
+        (1..accumulator.encrypted1_s_powers.len()).all(|index1| {
+            let negated1_s_power0 = E::negate1(accumulator.encrypted1_s_powers[0]);
             return (1..index1 + 1).all(|index2| {
-                crypto_pairing(
-                    accumulator.encrypted1_s_powers[index2],
-                    accumulator.encrypted2_s_powers[index1 - index2],
-                ) == expected_pairing
+                E::pairing_product_is_one(&[
+                    (
+                        accumulator.encrypted1_s_powers[index2],
+                        accumulator.encrypted2_s_powers[index1 - index2],
+                    ),
+                    (negated1_s_power0, accumulator.encrypted2_s_powers[index1]),
+                ])
             });
         });
+    if !both_vecs_of_s_powers_have_same_ratios {
+        return Err(CeremonyError::PowersAreNotGeometric { contribution });
+    }
 
-    return valid_alpha_relation
-        && both_encryptions_of_s_powers_match
-        && both_vecs_of_s_powers_have_same_ratios;
+    return Ok(());
 }
 
-fn is_valid_crs_ceremony_step(before: &CrsCeremonyValues, after: &CrsCeremonyValues) -> bool {
-    let alpha_was_transformed_by_stated_value =
-        crypto_pairing(
+fn is_valid_crs_ceremony_step<E: PairingEngine>(
+    before: &CrsCeremonyValues<E>,
+    after: &CrsCeremonyValues<E>,
+) -> bool {
+    let alpha_was_transformed_by_stated_value = E::pairing_product_is_one(&[
+        (
             after.step.encrypted1_alpha,
             before.accumulator.encrypted2_alpha,
-        ) == crypto_pairing(encrypt1(1), after.accumulator.encrypted2_alpha);
-
-    let every_s_power_was_transformed_by_stated_value =
-        (0..(POLYNOMIAL_DEGREE + 1) as usize).all(|index| {
-            crypto_pairing(
-                after.step.encrypted1_s_powers[index],
-                before.accumulator.encrypted2_s_powers[index],
-            ) == crypto_pairing(after.accumulator.encrypted1_s_powers[index], encrypt2(1))
+        ),
+        (
+            E::negate1(E::generator1()),
+            after.accumulator.encrypted2_alpha,
+        ),
+    ]);
+
+    let every_s_power_was_transformed_by_stated_value = (0..after.step.encrypted1_s_powers.len())
+        .all(|index| {
+            E::pairing_product_is_one(&[
+                (
+                    after.step.encrypted1_s_powers[index],
+                    before.accumulator.encrypted2_s_powers[index],
+                ),
+                (
+                    E::negate1(after.accumulator.encrypted1_s_powers[index]),
+                    E::generator2(),
+                ),
+            ])
         });
 
     let every_alpha_times_s_power_was_transformed_by_stated_value =
-        (0..(POLYNOMIAL_DEGREE + 1) as usize).all(|index| {
-            crypto_pairing(
-                before.accumulator.encrypted1_alpha_times_s_powers[index],
-                after.step.encrypted2_alpha_times_s_powers[index],
-            ) == crypto_pairing(
-                after.accumulator.encrypted1_alpha_times_s_powers[index],
-                encrypt2(1),
-            )
+        (0..after.step.encrypted2_alpha_times_s_powers.len()).all(|index| {
+            E::pairing_product_is_one(&[
+                (
+                    before.accumulator.encrypted1_alpha_times_s_powers[index],
+                    after.step.encrypted2_alpha_times_s_powers[index],
+                ),
+                (
+                    E::negate1(after.accumulator.encrypted1_alpha_times_s_powers[index]),
+                    E::generator2(),
+                ),
+            ])
         });
 
     return alpha_was_transformed_by_stated_value
         && every_s_power_was_transformed_by_stated_value
         && every_alpha_times_s_power_was_transformed_by_stated_value;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::toy_engine::ToyEngine;
+    use proptest::prelude::*;
+
+    proptest! {
+        // every run validates the whole transcript once per player, so fewer runs than the default
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn honest_ceremony_always_passes_validation(
+            secrets in prop::collection::vec((2..1000usize, 2..1000usize), 1..5),
+        ) {
+            let (first_s, first_alpha) = secrets[0];
+            let mut transcript: CrsCeremonyTranscript<ToyEngine> = CrsCeremonyTranscript {
+                history: vec![CrsPlayer::with_engine(first_s, first_alpha).start_crs_ceremony()],
+            };
+            for (s, alpha) in &secrets[1..] {
+                let next = CrsPlayer::with_engine(*s, *alpha)
+                    .continue_crs_ceremony(&transcript)
+                    .expect("honest transcript");
+                transcript.history.push(next);
+            }
+
+            prop_assert_eq!(validate_ceremony_transcript(&transcript), Ok(()));
+        }
+    }
+}
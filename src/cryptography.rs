@@ -1,36 +1,79 @@
+use std::{iter::Sum, ops::Add, sync::OnceLock};
+
 use polynomen::Poly;
 use zksnark::groth16::{
     fr::{G1Local, G2Local, GtLocal},
     EllipticEncryptable, FrLocal,
 };
 
+use crate::DegreeError;
+
 pub(crate) fn crypto_pairing(e_a: G1Local, e_b: G2Local) -> GtLocal {
     return FrLocal::pairing(e_a, e_b);
 }
 
+/// Checks `e(a_1, b_1) * e(a_2, b_2) * ... == 1`. Every pairing equation of the verifiers is written
+/// this way, an equality `e(a, b) == e(c, d)` as `e(a, b) * e(-c, d) == 1`.
+///
+/// The `bn` crate behind `zksnark` only exposes the fully reduced pairing, so here every pair costs a
+/// full pairing. `SubstrateBnEngine` runs one Miller loop per pair and a single final exponentiation.
+pub(crate) fn pairing_product_is_one(pairs: &[(G1Local, G2Local)]) -> bool {
+    let product = pairs
+        .iter()
+        .map(|(e_a, e_b)| crypto_pairing(*e_a, *e_b))
+        .fold(gt_identity(), |acc, gt| acc + gt); // GtLocal puts the group multiplication in its Add trait
+    return product == gt_identity();
+}
+
+/// pairing with the point at infinity short-circuits to the identity of Gt, so this is cheap
+fn gt_identity() -> GtLocal {
+    return crypto_pairing(encrypt1(0), encrypted2_one());
+}
+
 // G1 functions
 
 pub(crate) fn homomorphic1_eval_polynomial(
-    encrypted_x_powers: &Vec<G1Local>,
+    encrypted_x_powers: &[G1Local],
     polynomial: &Poly<usize>,
-) -> G1Local {
-    return polynomial
-        .coeffs()
-        .into_iter()
-        .zip(encrypted_x_powers)
-        .map(|(coeff, encrypted_s_power)| homomorphic1_multiply(encrypted_s_power, coeff))
-        .sum();
+) -> Result<G1Local, DegreeError> {
+    let coeffs = polynomial.coeffs();
+    let powers = lowest_powers(encrypted_x_powers, coeffs.len())?;
+    return Ok(multi_scalar_multiply1(powers, &coeffs));
+}
+
+/// for polynomials over the scalar field, evaluated as a linear combination of the powers
+pub(crate) fn homomorphic1_eval_field_polynomial(
+    encrypted_x_powers: &[G1Local],
+    polynomial: &[FrLocal],
+) -> Result<G1Local, DegreeError> {
+    let powers = lowest_powers(encrypted_x_powers, polynomial.len())?;
+    return homomorphic1_linear_combination(powers, polynomial);
+}
+
+/// `sum_i weights[i] * values[i]` computed on the encrypted values, one multiplication per weight
+/// as `FrLocal` gives `pippenger_multi_scalar_multiply` no bits to cut into windows
+pub(crate) fn homomorphic1_linear_combination(
+    encrypted_values: &[G1Local],
+    weights: &[FrLocal],
+) -> Result<G1Local, DegreeError> {
+    check_one_weight_per_value(encrypted_values, weights)?;
+    return Ok(weights
+        .iter()
+        .zip(encrypted_values)
+        .map(|(weight, encrypted_value)| weight.exp_encrypted_g1(*encrypted_value))
+        .sum());
 }
 
 /// uses the wrong value for the third polynomial coefficient
 pub(crate) fn erroneous1_homomorphic_eval_polynomial(
-    encrypted_x_powers: &Vec<G1Local>,
+    encrypted_x_powers: &[G1Local],
     polynomial: &Poly<usize>,
-) -> G1Local {
-    return polynomial
-        .coeffs()
+) -> Result<G1Local, DegreeError> {
+    let coeffs = polynomial.coeffs();
+    let powers = lowest_powers(encrypted_x_powers, coeffs.len())?;
+    return Ok(coeffs
         .into_iter()
-        .zip(encrypted_x_powers)
+        .zip(powers)
         .enumerate()
         .map(|(index, (coeff, encrypted_s_power))| {
             if index == 2 {
@@ -39,44 +82,89 @@ pub(crate) fn erroneous1_homomorphic_eval_polynomial(
                 homomorphic1_multiply(encrypted_s_power, coeff)
             }
         })
-        .sum();
+        .sum());
+}
+
+pub(crate) fn multi_scalar_multiply1(bases: &[G1Local], scalars: &[usize]) -> G1Local {
+    return pippenger_multi_scalar_multiply(bases, &usize_encodings(scalars), encrypt1(0));
 }
 
 pub(crate) fn homomorphic1_multiply(e_a: &G1Local, b: usize) -> G1Local {
     return FrLocal::from(b).exp_encrypted_g1(*e_a);
 }
 
+pub(crate) fn negate1(e_a: G1Local) -> G1Local {
+    return encrypt1(0) - e_a;
+}
+
 pub(crate) fn encrypt1(n: usize) -> G1Local {
-    return FrLocal::from(n).encrypt_g1();
+    return fixed_base_multiply(generator1_table(), n as u128);
 }
 
 pub(crate) fn mult_and_encrypt1(n: usize, m: usize) -> G1Local {
-    return (FrLocal::from(n) * FrLocal::from(m)).encrypt_g1();
+    return fixed_base_multiply(generator1_table(), n as u128 * m as u128);
+}
+
+pub(crate) fn encrypt1_field_element(x: FrLocal) -> G1Local {
+    return x.encrypt_g1();
+}
+
+/// the same value as `encrypt1(1)`, computed once
+pub(crate) fn encrypted1_one() -> G1Local {
+    static ENCRYPTED1_ONE: OnceLock<G1Local> = OnceLock::new();
+    return *ENCRYPTED1_ONE.get_or_init(|| FrLocal::from(1).encrypt_g1());
+}
+
+fn generator1_table() -> &'static FixedBaseTable<G1Local> {
+    static GENERATOR1_TABLE: OnceLock<FixedBaseTable<G1Local>> = OnceLock::new();
+    return GENERATOR1_TABLE.get_or_init(|| build_fixed_base_table(encrypted1_one()));
 }
 
 // G2 functions
 
 pub(crate) fn homomorphic2_eval_polynomial(
-    encrypted_x_powers: &Vec<G2Local>,
+    encrypted_x_powers: &[G2Local],
     polynomial: &Poly<usize>,
-) -> G2Local {
-    return polynomial
-        .coeffs()
-        .into_iter()
-        .zip(encrypted_x_powers)
-        .map(|(coeff, encrypted_s_power)| homomorphic2_multiply(encrypted_s_power, coeff))
-        .sum();
+) -> Result<G2Local, DegreeError> {
+    let coeffs = polynomial.coeffs();
+    let powers = lowest_powers(encrypted_x_powers, coeffs.len())?;
+    return Ok(multi_scalar_multiply2(powers, &coeffs));
+}
+
+/// for polynomials over the scalar field, evaluated as a linear combination of the powers
+pub(crate) fn homomorphic2_eval_field_polynomial(
+    encrypted_x_powers: &[G2Local],
+    polynomial: &[FrLocal],
+) -> Result<G2Local, DegreeError> {
+    let powers = lowest_powers(encrypted_x_powers, polynomial.len())?;
+    return homomorphic2_linear_combination(powers, polynomial);
+}
+
+/// `sum_i weights[i] * values[i]` computed on the encrypted values, one multiplication per weight
+/// as `FrLocal` gives `pippenger_multi_scalar_multiply` no bits to cut into windows
+pub(crate) fn homomorphic2_linear_combination(
+    encrypted_values: &[G2Local],
+    weights: &[FrLocal],
+) -> Result<G2Local, DegreeError> {
+    check_one_weight_per_value(encrypted_values, weights)?;
+    return Ok(weights
+        .iter()
+        .zip(encrypted_values)
+        .map(|(weight, encrypted_value)| weight.exp_encrypted_g2(*encrypted_value))
+        .sum());
 }
 
 /// uses the wrong value for the third polynomial coefficient
+#[allow(dead_code)]
 pub(crate) fn erroneous2_homomorphic_eval_polynomial(
-    encrypted_x_powers: &Vec<G2Local>,
+    encrypted_x_powers: &[G2Local],
     polynomial: &Poly<usize>,
-) -> G2Local {
-    return polynomial
-        .coeffs()
+) -> Result<G2Local, DegreeError> {
+    let coeffs = polynomial.coeffs();
+    let powers = lowest_powers(encrypted_x_powers, coeffs.len())?;
+    return Ok(coeffs
         .into_iter()
-        .zip(encrypted_x_powers)
+        .zip(powers)
         .enumerate()
         .map(|(index, (coeff, encrypted_s_power))| {
             if index == 2 {
@@ -85,7 +173,11 @@ pub(crate) fn erroneous2_homomorphic_eval_polynomial(
                 homomorphic2_multiply(encrypted_s_power, coeff)
             }
         })
-        .sum();
+        .sum());
+}
+
+pub(crate) fn multi_scalar_multiply2(bases: &[G2Local], scalars: &[usize]) -> G2Local {
+    return pippenger_multi_scalar_multiply(bases, &usize_encodings(scalars), encrypt2(0));
 }
 
 pub(crate) fn homomorphic2_multiply(e_a: &G2Local, b: usize) -> G2Local {
@@ -93,9 +185,353 @@ pub(crate) fn homomorphic2_multiply(e_a: &G2Local, b: usize) -> G2Local {
 }
 
 pub(crate) fn encrypt2(n: usize) -> G2Local {
-    return FrLocal::from(n).encrypt_g2();
+    return fixed_base_multiply(generator2_table(), n as u128);
 }
 
 pub(crate) fn mult_and_encrypt2(n: usize, m: usize) -> G2Local {
-    return (FrLocal::from(n) * FrLocal::from(m)).encrypt_g2();
+    return fixed_base_multiply(generator2_table(), n as u128 * m as u128);
+}
+
+pub(crate) fn encrypt2_field_element(x: FrLocal) -> G2Local {
+    return x.encrypt_g2();
+}
+
+/// the same value as `encrypt2(1)`, computed once
+pub(crate) fn encrypted2_one() -> G2Local {
+    static ENCRYPTED2_ONE: OnceLock<G2Local> = OnceLock::new();
+    return *ENCRYPTED2_ONE.get_or_init(|| FrLocal::from(1).encrypt_g2());
+}
+
+fn generator2_table() -> &'static FixedBaseTable<G2Local> {
+    static GENERATOR2_TABLE: OnceLock<FixedBaseTable<G2Local>> = OnceLock::new();
+    return GENERATOR2_TABLE.get_or_init(|| build_fixed_base_table(encrypted2_one()));
+}
+
+// Length checks, shared by G1 and G2
+
+/// the powers `x^0` to `x^(coefficients - 1)`, or an error if the polynomial goes beyond them
+pub(crate) fn lowest_powers<G>(
+    encrypted_x_powers: &[G],
+    coefficients: usize,
+) -> Result<&[G], DegreeError> {
+    return encrypted_x_powers
+        .get(..coefficients)
+        .ok_or(DegreeError::MoreCoefficientsThanPowers {
+            coefficients,
+            powers: encrypted_x_powers.len(),
+        });
+}
+
+pub(crate) fn check_one_weight_per_value<G, W>(
+    encrypted_values: &[G],
+    weights: &[W],
+) -> Result<(), DegreeError> {
+    if weights.len() != encrypted_values.len() {
+        return Err(DegreeError::WeightCountMismatch {
+            weights: weights.len(),
+            encrypted_values: encrypted_values.len(),
+        });
+    }
+    return Ok(());
+}
+
+// Fixed-base multiplication of the generators, shared by G1 and G2
+
+const FIXED_BASE_WINDOW_BITS: usize = 4;
+
+/// wide enough for the product of two `usize` values, see `mult_and_encrypt1`
+const FIXED_BASE_SCALAR_BITS: usize = 128;
+
+/// `table[window][digit] == digit * 2^(window * FIXED_BASE_WINDOW_BITS) * base`
+type FixedBaseTable<G> = Vec<Vec<G>>;
+
+fn build_fixed_base_table<G>(base: G) -> FixedBaseTable<G>
+where
+    G: Copy + Add<Output = G> + Sum,
+{
+    let identity: G = std::iter::empty().sum();
+    let number_of_windows = FIXED_BASE_SCALAR_BITS / FIXED_BASE_WINDOW_BITS;
+
+    let mut table = Vec::with_capacity(number_of_windows);
+    let mut window_base = base;
+    for _ in 0..number_of_windows {
+        let mut row = Vec::with_capacity(1 << FIXED_BASE_WINDOW_BITS);
+        let mut multiple = identity;
+        for _ in 0..(1 << FIXED_BASE_WINDOW_BITS) {
+            row.push(multiple);
+            multiple = multiple + window_base;
+        }
+        // after the loop `multiple` is 2^FIXED_BASE_WINDOW_BITS * window_base, the next window's base
+        window_base = multiple;
+        table.push(row);
+    }
+
+    return table;
+}
+
+/// one group addition per window and no doublings
+fn fixed_base_multiply<G>(table: &FixedBaseTable<G>, scalar: u128) -> G
+where
+    G: Copy + Add<Output = G> + Sum,
+{
+    let digit_mask = (1 << FIXED_BASE_WINDOW_BITS) - 1;
+    return table
+        .iter()
+        .enumerate()
+        .map(|(window, row)| {
+            row[((scalar >> (window * FIXED_BASE_WINDOW_BITS)) & digit_mask) as usize]
+        })
+        .sum();
+}
+
+// Multi-scalar multiplication, shared by G1 and G2
+
+/// Computes `sum_i scalars[i] * bases[i]` with Pippenger's bucket method.
+///
+/// Each scalar is given by its canonical big-endian encoding, a usize's `to_be_bytes` or a field
+/// element's `EncodableEngine::scalar_to_bytes`, and cut into windows of `c` bits. Within a window
+/// every base is added once into the bucket named by its digit, and the buckets are combined with a
+/// running sum, so a window costs about `n + 2^c` group additions rather than `n` scalar
+/// multiplications.
+///
+/// The group's identity is passed in, as the bn crate's points don't implement `Sum`.
+pub(crate) fn pippenger_multi_scalar_multiply<G, S>(bases: &[G], scalars: &[S], identity: G) -> G
+where
+    G: Copy + Add<Output = G>,
+    S: AsRef<[u8]>,
+{
+    assert_eq!(bases.len(), scalars.len(), "one scalar per base");
+
+    let scalar_bits = scalars
+        .iter()
+        .map(|scalar| bit_length(scalar.as_ref()))
+        .max()
+        .unwrap_or(0);
+    if scalar_bits == 0 {
+        return identity;
+    }
+
+    let window_bits = pippenger_window_bits(bases.len());
+    let number_of_windows = (scalar_bits + window_bits - 1) / window_bits;
+
+    let mut result = identity;
+    for window in (0..number_of_windows).rev() {
+        for _ in 0..window_bits {
+            result = result + result;
+        }
+
+        // bucket k holds the sum of the bases whose digit in this window is k + 1
+        let mut buckets: Vec<G> = vec![identity; (1 << window_bits) - 1];
+        for (base, scalar) in bases.iter().zip(scalars) {
+            let digit = window_digit(scalar.as_ref(), window * window_bits, window_bits);
+            if digit != 0 {
+                buckets[digit - 1] = buckets[digit - 1] + *base;
+            }
+        }
+
+        // sum_k (k + 1) * bucket[k], computed as a sum of suffix sums
+        let mut running_sum = identity;
+        let mut window_sum = identity;
+        for bucket in buckets.into_iter().rev() {
+            running_sum = running_sum + bucket;
+            window_sum = window_sum + running_sum;
+        }
+
+        result = result + window_sum;
+    }
+
+    return result;
+}
+
+/// the big-endian encodings `pippenger_multi_scalar_multiply` takes, for scalars that fit a usize
+pub(crate) fn usize_encodings(scalars: &[usize]) -> Vec<[u8; USIZE_BYTES]> {
+    return scalars.iter().map(|scalar| scalar.to_be_bytes()).collect();
+}
+
+const USIZE_BYTES: usize = (usize::BITS / 8) as usize;
+
+/// the position of the highest set bit plus one, 0 for an encoding of zero
+fn bit_length(big_endian: &[u8]) -> usize {
+    return match big_endian.iter().position(|byte| *byte != 0) {
+        Some(index) => (big_endian.len() - index) * 8 - big_endian[index].leading_zeros() as usize,
+        None => 0,
+    };
+}
+
+/// the `width` bits of a big-endian encoding from `offset` bits above its lowest bit, with the bits
+/// beyond the encoding read as zero
+fn window_digit(big_endian: &[u8], offset: usize, width: usize) -> usize {
+    let mut digit = 0;
+    for bit in (offset..offset + width).rev() {
+        let byte = match big_endian.len().checked_sub(bit / 8 + 1) {
+            Some(index) => big_endian[index],
+            None => 0,
+        };
+        digit = (digit << 1) | usize::from((byte >> (bit % 8)) & 1);
+    }
+    return digit;
+}
+
+fn pippenger_window_bits(number_of_terms: usize) -> usize {
+    if number_of_terms < 32 {
+        return 3;
+    }
+    // roughly ln(n), which balances the per-term additions against the per-bucket additions
+    return (usize::BITS - number_of_terms.leading_zeros()) as usize * 69 / 100 + 2;
+}
+
+#[cfg(test)]
+mod tests {
+    use polynomen::Poly;
+    use proptest::prelude::*;
+    use zksnark::groth16::fr::{G1Local, G2Local};
+
+    use super::*;
+
+    fn naive1_eval_polynomial(encrypted_x_powers: &[G1Local], polynomial: &Poly<usize>) -> G1Local {
+        return polynomial
+            .coeffs()
+            .into_iter()
+            .zip(encrypted_x_powers)
+            .map(|(coeff, encrypted_s_power)| homomorphic1_multiply(encrypted_s_power, coeff))
+            .sum();
+    }
+
+    fn naive2_eval_polynomial(encrypted_x_powers: &[G2Local], polynomial: &Poly<usize>) -> G2Local {
+        return polynomial
+            .coeffs()
+            .into_iter()
+            .zip(encrypted_x_powers)
+            .map(|(coeff, encrypted_s_power)| homomorphic2_multiply(encrypted_s_power, coeff))
+            .sum();
+    }
+
+    /// a number of coefficients in `lengths`, each a bit, small, or anywhere in a usize, so that
+    /// some windows of the multi-scalar multiplication are empty and others full
+    fn polynomial(lengths: std::ops::RangeInclusive<usize>) -> impl Strategy<Value = Poly<usize>> {
+        let coeff = prop_oneof![0..2usize, 0..1000usize, any::<usize>()];
+        return prop::collection::vec(coeff, lengths)
+            .prop_map(|coeffs| Poly::new_from_coeffs(&coeffs));
+    }
+
+    #[test]
+    fn fixed_base_encryption_matches_generator_multiplication() {
+        for n in [0, 1, 2, 15, 16, 17, 3412992, usize::MAX] {
+            assert!(encrypt1(n) == FrLocal::from(n).encrypt_g1());
+            assert!(encrypt2(n) == FrLocal::from(n).encrypt_g2());
+        }
+        assert!(encrypted1_one() == FrLocal::from(1).encrypt_g1());
+        assert!(encrypted2_one() == FrLocal::from(1).encrypt_g2());
+    }
+
+    #[test]
+    fn fixed_base_encryption_of_products_does_not_overflow() {
+        for (n, m) in [(12, 13), (usize::MAX, 2), (usize::MAX, usize::MAX)] {
+            let expected_product = FrLocal::from(n) * FrLocal::from(m);
+            assert!(mult_and_encrypt1(n, m) == expected_product.encrypt_g1());
+            assert!(mult_and_encrypt2(n, m) == expected_product.encrypt_g2());
+        }
+    }
+
+    #[test]
+    fn pairing_product_checks_pairing_equalities() {
+        let e_a = encrypt1(1024);
+        let e_b = encrypt2(3333);
+
+        assert!(pairing_product_is_one(&[
+            (e_a, e_b),
+            (negate1(encrypt1(1024 * 3333)), encrypted2_one())
+        ]));
+        assert!(!pairing_product_is_one(&[
+            (e_a, e_b),
+            (negate1(encrypt1(1024 * 3334)), encrypted2_one())
+        ]));
+        assert!(!pairing_product_is_one(&[(e_a, e_b)]));
+        assert!(pairing_product_is_one(&[]));
+    }
+
+    proptest! {
+        // every run multiplies each power twice, and G2 multiplications are slow
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn msm_matches_naive_evaluation_in_g1(polynomial in polynomial(1..=40)) {
+            let s: usize = 11;
+            let encrypted1_s_powers: Vec<G1Local> =
+                (0..40).map(|k| encrypt1(s.pow(k % 10))).collect();
+
+            prop_assert!(
+                homomorphic1_eval_polynomial(&encrypted1_s_powers, &polynomial).ok()
+                    == Some(naive1_eval_polynomial(&encrypted1_s_powers, &polynomial))
+            );
+        }
+
+        #[test]
+        fn msm_matches_naive_evaluation_in_g2(polynomial in polynomial(1..=33)) {
+            let s: usize = 13;
+            let encrypted2_s_powers: Vec<G2Local> =
+                (0..33).map(|k| encrypt2(s.pow(k % 10))).collect();
+
+            prop_assert!(
+                homomorphic2_eval_polynomial(&encrypted2_s_powers, &polynomial).ok()
+                    == Some(naive2_eval_polynomial(&encrypted2_s_powers, &polynomial))
+            );
+        }
+    }
+
+    #[test]
+    fn evaluation_uses_the_lowest_powers_for_a_shorter_polynomial() {
+        let encrypted1_s_powers: Vec<G1Local> = (0..5).map(|k| encrypt1(5usize.pow(k))).collect();
+        let polynomial: Poly<usize> = Poly::new_from_coeffs(&[3, 4, 5]);
+
+        assert!(
+            homomorphic1_eval_polynomial(&encrypted1_s_powers, &polynomial).ok()
+                == Some(encrypt1(3 + 4 * 5 + 5 * 25))
+        );
+    }
+
+    #[test]
+    fn evaluation_refuses_a_polynomial_beyond_the_powers() {
+        let encrypted1_s_powers: Vec<G1Local> = (0..3).map(|k| encrypt1(5usize.pow(k))).collect();
+        let polynomial: Poly<usize> = Poly::new_from_coeffs(&[3, 4, 5, 6, 7]);
+
+        assert_eq!(
+            homomorphic1_eval_polynomial(&encrypted1_s_powers, &polynomial).err(),
+            Some(DegreeError::MoreCoefficientsThanPowers {
+                coefficients: 5,
+                powers: 3
+            })
+        );
+        assert_eq!(
+            homomorphic1_eval_field_polynomial(&encrypted1_s_powers, &[FrLocal::from(3); 4]).err(),
+            Some(DegreeError::MoreCoefficientsThanPowers {
+                coefficients: 4,
+                powers: 3
+            })
+        );
+    }
+
+    #[test]
+    fn linear_combination_refuses_a_weight_count_other_than_the_value_count() {
+        let encrypted_values: Vec<G1Local> = (1..4).map(encrypt1).collect();
+
+        assert!(
+            homomorphic1_linear_combination(&encrypted_values, &[FrLocal::from(2); 3]).ok()
+                == Some(encrypt1(2 + 4 + 6))
+        );
+        assert_eq!(
+            homomorphic1_linear_combination(&encrypted_values, &[FrLocal::from(2); 2]).err(),
+            Some(DegreeError::WeightCountMismatch {
+                weights: 2,
+                encrypted_values: 3
+            })
+        );
+    }
+
+    #[test]
+    fn msm_of_zero_scalars_is_identity() {
+        let bases: Vec<G1Local> = (1..5).map(encrypt1).collect();
+        assert!(multi_scalar_multiply1(&bases, &[0, 0, 0, 0]) == encrypt1(0));
+        assert!(multi_scalar_multiply1(&[], &[]) == encrypt1(0));
+    }
 }
@@ -0,0 +1,241 @@
+// An alternative backend that hands our QAPs to the zksnark crate's own Groth16 implementation, so
+// that its proofs can be compared with the ones of the Petkus-style protocol.
+
+use zksnark::{
+    field::{FieldIdentity, Polynomial},
+    groth16::{
+        self,
+        circuit::dummy_rep::DummyRep,
+        coefficient_poly::CoefficientPoly,
+        fr::{G1Local, G2Local},
+        FrLocal, SigmaG1, SigmaG2, QAP,
+    },
+};
+
+use crate::{
+    crs_player::{validate_public_inputs, PublicInputError},
+    qap::QuadraticArithmeticProgram,
+};
+
+/// three group elements `(A, B, C)`, whatever the size of the computation
+pub(crate) type Groth16Proof = groth16::Proof<G1Local, G2Local>;
+
+/// the points of G1 and of G2 in a `Groth16Proof`: `A` and `C` in G1, `B` in G2. The crate keeps
+/// them private, so they are counted here rather than read off a proof.
+pub(crate) const GROTH16_PROOF_POINTS: (usize, usize) = (2, 1);
+
+/// The zksnark crate's QAP and common reference string for one statement.
+///
+/// The crate's `verify` takes the reference string by value and it can't be cloned, so each key
+/// verifies a single proof.
+pub(crate) struct Groth16Key {
+    qap: QAP<CoefficientPoly<FrLocal>>,
+    crs: (SigmaG1<G1Local>, SigmaG2<G2Local>),
+    // the crate wants the constant one first, then the public inputs, then the private variables
+    variable_order: Vec<usize>,
+    number_of_public_inputs: usize,
+}
+
+impl Groth16Key {
+    /// Runs the crate's setup, which draws its own secrets, on the QAP. `public_inputs` are the
+    /// indices of the variables the verifier supplies, as for `CrsPlayer::create_computation_keys`,
+    /// and are refused for the same reasons.
+    pub(crate) fn new(
+        qap: &QuadraticArithmeticProgram,
+        public_inputs: &[usize],
+    ) -> Result<Self, PublicInputError> {
+        validate_public_inputs(qap, public_inputs)?;
+        let variable_order: Vec<usize> = std::iter::once(0)
+            .chain(public_inputs.iter().copied())
+            .chain(
+                (1..qap.number_of_variables()).filter(|variable| !public_inputs.contains(variable)),
+            )
+            .collect();
+
+        let roots: Vec<FrLocal> = (1..qap.number_of_operations() + 1)
+            .map(FrLocal::from)
+            .collect();
+        let values_at_roots =
+            |polynomials: &[CoefficientPoly<FrLocal>]| -> Vec<Vec<(FrLocal, FrLocal)>> {
+                return variable_order
+                    .iter()
+                    .map(|variable| {
+                        roots
+                            .iter()
+                            .map(|root| (*root, polynomials[*variable].evaluate(*root)))
+                            .filter(|(_, value)| *value != FrLocal::zero())
+                            .collect()
+                    })
+                    .collect();
+            };
+
+        let qap: QAP<CoefficientPoly<FrLocal>> = DummyRep {
+            u: values_at_roots(qap.left_variable_polynomials()),
+            v: values_at_roots(qap.right_variable_polynomials()),
+            w: values_at_roots(qap.output_variable_polynomials()),
+            roots,
+            input: public_inputs.len(),
+        }
+        .into();
+        let crs = groth16::setup(&qap);
+
+        return Ok(Self {
+            qap,
+            crs,
+            variable_order,
+            number_of_public_inputs: public_inputs.len(),
+        });
+    }
+}
+
+pub(crate) struct Groth16Prover {
+    my_secret_witness: Vec<FrLocal>,
+}
+
+impl Groth16Prover {
+    /// `witness` is indexed like the variables of our QAP
+    pub(crate) fn new(witness: Vec<FrLocal>) -> Self {
+        Self {
+            my_secret_witness: witness,
+        }
+    }
+
+    pub(crate) fn prove(&self, key: &Groth16Key) -> Groth16Proof {
+        let weights: Vec<FrLocal> = key
+            .variable_order
+            .iter()
+            .map(|variable| self.my_secret_witness[*variable])
+            .collect();
+        return groth16::prove(&key.qap, (&key.crs.0, &key.crs.1), &weights);
+    }
+}
+
+/// A single check `e(A, B) == e(alpha, beta) * e(public inputs, gamma) * e(C, delta)`, four pairings
+/// however many operations there are.
+pub(crate) fn verify_groth16(
+    key: Groth16Key,
+    public_inputs: &[FrLocal],
+    proof: Groth16Proof,
+) -> bool {
+    // the crate zips the inputs with its keys, so it would silently ignore missing ones
+    if public_inputs.len() != key.number_of_public_inputs {
+        return false;
+    }
+    return groth16::verify(&key.qap, key.crs, public_inputs, proof);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        circuit::cubic_circuit,
+        crs_player::{ComputationSecrets, CrsPlayer},
+        pairing_engine::{BnEngine, EncodableEngine, PairingEngine, ZksnarkEngine},
+        prover::ComputationProver,
+    };
+
+    /// The bytes on the wire of a proof with that many points. The zksnark crate keeps its points
+    /// opaque, so they are sized as bn encodes the same points.
+    fn encoded_proof_size(g1_points: usize, g2_points: usize) -> usize {
+        return g1_points * BnEngine::g1_to_bytes(&BnEngine::generator1()).len()
+            + g2_points * BnEngine::g2_to_bytes(&BnEngine::generator2()).len();
+    }
+
+    fn groth16_proof_verifies(witness: Vec<FrLocal>, public_output: usize) -> bool {
+        let circuit = cubic_circuit();
+        let qap = circuit.compile();
+        let out = circuit.variable_index("out").expect("variable");
+        let key = Groth16Key::new(&qap, &[out]).expect("valid public inputs");
+
+        let proof = Groth16Prover::new(witness).prove(&key);
+        return verify_groth16(key, &[FrLocal::from(public_output)], proof);
+    }
+
+    #[test]
+    fn honest_groth16_proof_is_valid() {
+        let witness = cubic_circuit()
+            .compute_witness(&[("x", 3)])
+            .expect("x is the circuit's input");
+
+        assert!(groth16_proof_verifies(witness, 30));
+    }
+
+    #[test]
+    fn groth16_proof_is_invalid_for_another_public_output() {
+        let witness = cubic_circuit()
+            .compute_witness(&[("x", 3)])
+            .expect("x is the circuit's input");
+
+        assert!(!groth16_proof_verifies(witness, 31));
+    }
+
+    #[test]
+    fn groth16_proof_of_unsatisfying_witness_is_invalid() {
+        let circuit = cubic_circuit();
+        let mut witness = circuit
+            .compute_witness(&[("x", 3)])
+            .expect("x is the circuit's input");
+        witness[circuit.variable_index("x_cubed").expect("variable")] = FrLocal::from(28);
+
+        assert!(!groth16_proof_verifies(witness, 30));
+    }
+
+    #[test]
+    fn groth16_key_refuses_invalid_public_inputs() {
+        let circuit = cubic_circuit();
+        let qap = circuit.compile();
+        let out = circuit.variable_index("out").expect("variable");
+        let number_of_variables = qap.number_of_variables();
+        let error_of = |public_inputs: &[usize]| Groth16Key::new(&qap, public_inputs).err();
+
+        assert_eq!(error_of(&[0]), Some(PublicInputError::ConstantOneListed));
+        assert_eq!(
+            error_of(&[number_of_variables]),
+            Some(PublicInputError::VariableOutOfRange {
+                variable: number_of_variables,
+                number_of_variables
+            })
+        );
+        assert_eq!(
+            error_of(&[out, out]),
+            Some(PublicInputError::DuplicateVariable { variable: out })
+        );
+    }
+
+    #[test]
+    fn groth16_proof_is_smaller_than_a_computation_proof() {
+        let circuit = cubic_circuit();
+        let qap = circuit.compile();
+        let out = circuit.variable_index("out").expect("variable");
+        let witness = circuit
+            .compute_witness(&[("x", 3)])
+            .expect("x is the circuit's input");
+        let (proving_key, _) = CrsPlayer::<ZksnarkEngine>::with_engine(31, 32)
+            .create_computation_keys(
+                &qap,
+                &[out],
+                &ComputationSecrets {
+                    alpha_l: 33,
+                    alpha_r: 34,
+                    alpha_o: 35,
+                    beta: 36,
+                    gamma: 37,
+                    rho_l: 38,
+                    rho_r: 39,
+                },
+            )
+            .expect("valid public inputs");
+        let proof = ComputationProver::new(&qap, witness)
+            .prove(&proving_key)
+            .expect("a satisfying witness");
+
+        // the computation proof's verification time is compared by the ignored tests of
+        // `benchmarks.rs`
+        let (g1_points, g2_points) = proof.points();
+        let (groth16_g1_points, groth16_g2_points) = GROTH16_PROOF_POINTS;
+        assert!(
+            encoded_proof_size(groth16_g1_points, groth16_g2_points)
+                < encoded_proof_size(g1_points.len(), g2_points.len())
+        );
+    }
+}
@@ -0,0 +1,337 @@
+// Hashing to G1 and G2 of BN254, the curve the bn crate implements, for group elements nobody knows
+// the discrete logarithm of, such as beacons and nothing-up-my-sleeve generators.
+//
+// Both use try-and-increment: hash to an x coordinate until x^3 + b is a square. It is simple but
+// not constant time, which is fine for public inputs. bn keeps its field arithmetic private, so the
+// base field and its quadratic extension are implemented here on big integers, and the resulting
+// point goes back to bn through its encoding, which checks it is on the curve.
+
+use std::sync::OnceLock;
+
+use num_bigint::BigUint;
+use sha2::{Digest, Sha256};
+
+use crate::serialization::{g1_from_bytes, g2_from_bytes};
+
+const BASE_FIELD_MODULUS: &str =
+    "21888242871839275222246405745257275088696311157297823662689037894645226208583";
+
+/// `2q - r`, the number of points of the twist divided by the order of G2
+const G2_COFACTOR: &str =
+    "21888242871839275222246405745257275088844257914179612981679871602714643921549";
+
+/// Hashes `message` to a point of G1, a different point for each domain separation tag.
+///
+/// Every point of the curve is in G1, its cofactor is 1.
+pub(crate) fn hash_to_g1(domain_separation_tag: &str, message: &[u8]) -> bn::G1 {
+    let b = BigUint::from(3u32);
+    for counter in 0u32.. {
+        let x = hash_to_base_field(domain_separation_tag, message, counter, 0);
+        let y_squared = fq_add(&fq_mul(&fq_mul(&x, &x), &x), &b);
+        if let Some(y) = fq_sqrt(&y_squared) {
+            let bytes = [vec![4], to_fixed_bytes(&x, 32), to_fixed_bytes(&y, 32)].concat();
+            return g1_from_bytes(&bytes).expect("the point is on the curve");
+        }
+    }
+    unreachable!("about half of the x coordinates are on the curve");
+}
+
+/// Hashes `message` to a point of G2, a different point for each domain separation tag.
+///
+/// The point found on the twist is multiplied by the cofactor to land in the order-r subgroup.
+pub(crate) fn hash_to_g2(domain_separation_tag: &str, message: &[u8]) -> bn::G2 {
+    let b = twist_coefficient_b();
+    for counter in 0u32.. {
+        let x = Fq2 {
+            c0: hash_to_base_field(domain_separation_tag, message, counter, 0),
+            c1: hash_to_base_field(domain_separation_tag, message, counter, 1),
+        };
+        let y_squared = x.mul(&x).mul(&x).add(&b);
+        let Some(y) = y_squared.sqrt() else {
+            continue;
+        };
+        let Some((x, y)) = twist_multiply(&Some((x, y)), g2_cofactor()) else {
+            continue;
+        };
+        let bytes = [vec![4], x.to_bytes(), y.to_bytes()].concat();
+        return g2_from_bytes(&bytes).expect("the point is in G2");
+    }
+    unreachable!("about half of the x coordinates are on the twist");
+}
+
+fn q() -> &'static BigUint {
+    static MODULUS: OnceLock<BigUint> = OnceLock::new();
+    return MODULUS.get_or_init(|| BASE_FIELD_MODULUS.parse().expect("decimal"));
+}
+
+fn g2_cofactor() -> &'static BigUint {
+    static COFACTOR: OnceLock<BigUint> = OnceLock::new();
+    return COFACTOR.get_or_init(|| G2_COFACTOR.parse().expect("decimal"));
+}
+
+/// 64 bytes of SHA-256 output reduced modulo q, so the result is close to uniform
+fn hash_to_base_field(
+    domain_separation_tag: &str,
+    message: &[u8],
+    counter: u32,
+    coordinate: u8,
+) -> BigUint {
+    let mut wide_bytes = vec![];
+    for block in 0u8..2 {
+        let mut hasher = Sha256::new();
+        hasher.update((domain_separation_tag.len() as u64).to_be_bytes());
+        hasher.update(domain_separation_tag.as_bytes());
+        hasher.update((message.len() as u64).to_be_bytes());
+        hasher.update(message);
+        hasher.update(counter.to_be_bytes());
+        hasher.update([coordinate, block]);
+        wide_bytes.extend(hasher.finalize());
+    }
+    return BigUint::from_bytes_be(&wide_bytes) % q();
+}
+
+/// big-endian, left padded with zeros
+fn to_fixed_bytes(n: &BigUint, length: usize) -> Vec<u8> {
+    let bytes = n.to_bytes_be();
+    return [vec![0; length - bytes.len()], bytes].concat();
+}
+
+fn fq_add(a: &BigUint, b: &BigUint) -> BigUint {
+    return (a + b) % q();
+}
+
+fn fq_sub(a: &BigUint, b: &BigUint) -> BigUint {
+    return (a + q() - b) % q();
+}
+
+fn fq_mul(a: &BigUint, b: &BigUint) -> BigUint {
+    return (a * b) % q();
+}
+
+fn fq_inverse(a: &BigUint) -> BigUint {
+    return a.modpow(&(q() - 2u32), q());
+}
+
+/// `q = 3 mod 4`, so `a^((q+1)/4)` is a square root of `a` whenever it has one. Of the two roots,
+/// the one below `q/2` is returned.
+fn fq_sqrt(a: &BigUint) -> Option<BigUint> {
+    let root = a.modpow(&((q() + 1u32) / 4u32), q());
+    if fq_mul(&root, &root) != *a {
+        return None;
+    }
+    return Some(std::cmp::min(root.clone(), q() - &root));
+}
+
+/// `c0 + c1 u` with `u^2 = -1`, the field the coordinates of G2 are in
+#[derive(Clone, PartialEq)]
+struct Fq2 {
+    c0: BigUint,
+    c1: BigUint,
+}
+
+impl Fq2 {
+    fn zero() -> Self {
+        Self {
+            c0: BigUint::from(0u32),
+            c1: BigUint::from(0u32),
+        }
+    }
+
+    fn one() -> Self {
+        Self {
+            c0: BigUint::from(1u32),
+            c1: BigUint::from(0u32),
+        }
+    }
+
+    fn from_base_field(c0: u32) -> Self {
+        Self {
+            c0: BigUint::from(c0),
+            c1: BigUint::from(0u32),
+        }
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        Self {
+            c0: fq_add(&self.c0, &other.c0),
+            c1: fq_add(&self.c1, &other.c1),
+        }
+    }
+
+    fn sub(&self, other: &Self) -> Self {
+        Self {
+            c0: fq_sub(&self.c0, &other.c0),
+            c1: fq_sub(&self.c1, &other.c1),
+        }
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        Self {
+            c0: fq_sub(&fq_mul(&self.c0, &other.c0), &fq_mul(&self.c1, &other.c1)),
+            c1: fq_add(&fq_mul(&self.c0, &other.c1), &fq_mul(&self.c1, &other.c0)),
+        }
+    }
+
+    /// `(c0 - c1 u) / (c0^2 + c1^2)`
+    fn inverse(&self) -> Self {
+        let norm_inverse = fq_inverse(&fq_add(
+            &fq_mul(&self.c0, &self.c0),
+            &fq_mul(&self.c1, &self.c1),
+        ));
+        Self {
+            c0: fq_mul(&self.c0, &norm_inverse),
+            c1: fq_mul(&fq_sub(&BigUint::from(0u32), &self.c1), &norm_inverse),
+        }
+    }
+
+    fn pow(&self, exponent: &BigUint) -> Self {
+        let mut result = Self::one();
+        for bit in (0..exponent.bits()).rev() {
+            result = result.mul(&result);
+            if exponent.bit(bit) {
+                result = result.mul(self);
+            }
+        }
+        return result;
+    }
+
+    /// Algorithm 9 of Adj and Rodríguez-Henríquez, "Square root computation over even extension
+    /// fields", for `q = 3 mod 4`
+    fn sqrt(&self) -> Option<Self> {
+        let minus_one = Self::zero().sub(&Self::one());
+        let a1 = self.pow(&((q() - 3u32) / 4u32));
+        let alpha = a1.mul(&a1).mul(self);
+        let x0 = a1.mul(self);
+
+        let root = if alpha == minus_one {
+            let u = Self {
+                c0: BigUint::from(0u32),
+                c1: BigUint::from(1u32),
+            };
+            u.mul(&x0)
+        } else {
+            alpha.add(&Self::one()).pow(&((q() - 1u32) / 2u32)).mul(&x0)
+        };
+
+        if root.mul(&root) != *self {
+            return None;
+        }
+        return Some(root);
+    }
+
+    /// as bn encodes it: `c1 q + c0` as a 64 byte big-endian integer
+    fn to_bytes(&self) -> Vec<u8> {
+        return to_fixed_bytes(&(&self.c1 * q() + &self.c0), 64);
+    }
+}
+
+/// `b' = 3 / (9 + u)` of the twist `y^2 = x^3 + b'`
+fn twist_coefficient_b() -> Fq2 {
+    let nine_plus_u = Fq2 {
+        c0: BigUint::from(9u32),
+        c1: BigUint::from(1u32),
+    };
+    return Fq2::from_base_field(3).mul(&nine_plus_u.inverse());
+}
+
+/// an affine point of the twist, `None` for the point at infinity
+type TwistPoint = Option<(Fq2, Fq2)>;
+
+fn twist_add(p: &TwistPoint, r: &TwistPoint) -> TwistPoint {
+    let (Some((x1, y1)), Some((x2, y2))) = (p, r) else {
+        return p.clone().or(r.clone());
+    };
+    let slope = if x1 == x2 {
+        if y1 != y2 || *y1 == Fq2::zero() {
+            return None;
+        }
+        // the tangent, when doubling
+        Fq2::from_base_field(3)
+            .mul(x1)
+            .mul(x1)
+            .mul(&Fq2::from_base_field(2).mul(y1).inverse())
+    } else {
+        y2.sub(y1).mul(&x2.sub(x1).inverse())
+    };
+
+    let x3 = slope.mul(&slope).sub(x1).sub(x2);
+    let y3 = slope.mul(&x1.sub(&x3)).sub(y1);
+    return Some((x3, y3));
+}
+
+fn twist_multiply(p: &TwistPoint, scalar: &BigUint) -> TwistPoint {
+    let mut result = None;
+    for bit in (0..scalar.bits()).rev() {
+        result = twist_add(&result, &result);
+        if scalar.bit(bit) {
+            result = twist_add(&result, p);
+        }
+    }
+    return result;
+}
+
+#[cfg(test)]
+mod tests {
+    use bn::Group;
+
+    use super::*;
+
+    const TAG: &str = "learning-zksnarks test";
+
+    /// `(r - 1) P + P` is the point at infinity exactly when `P` has order dividing r
+    fn is_in_order_r_subgroup1(p: bn::G1) -> bool {
+        return p * -bn::Fr::one() + p == bn::G1::zero();
+    }
+
+    fn is_in_order_r_subgroup2(p: bn::G2) -> bool {
+        return p * -bn::Fr::one() + p == bn::G2::zero();
+    }
+
+    #[test]
+    fn hashed_points_are_in_the_right_subgroups() {
+        for message in [&b""[..], b"a", b"beacon 2024-01-01"] {
+            let p = hash_to_g1(TAG, message);
+            assert!(p != bn::G1::zero());
+            assert!(is_in_order_r_subgroup1(p));
+
+            let p = hash_to_g2(TAG, message);
+            assert!(p != bn::G2::zero());
+            assert!(is_in_order_r_subgroup2(p));
+        }
+    }
+
+    #[test]
+    fn hashing_is_deterministic_and_domain_separated() {
+        assert!(hash_to_g1(TAG, b"a") == hash_to_g1(TAG, b"a"));
+        assert!(hash_to_g1(TAG, b"a") != hash_to_g1(TAG, b"b"));
+        assert!(hash_to_g1(TAG, b"a") != hash_to_g1("another tag", b"a"));
+
+        assert!(hash_to_g2(TAG, b"a") == hash_to_g2(TAG, b"a"));
+        assert!(hash_to_g2(TAG, b"a") != hash_to_g2(TAG, b"b"));
+        assert!(hash_to_g2(TAG, b"a") != hash_to_g2("another tag", b"a"));
+    }
+
+    #[test]
+    fn uncleared_twist_point_is_rejected_by_bn() {
+        let b = twist_coefficient_b();
+        let (x, y) = (0..)
+            .map(Fq2::from_base_field)
+            .find_map(|x| x.mul(&x).mul(&x).add(&b).sqrt().map(|y| (x, y)))
+            .expect("a point of the twist");
+
+        let bytes = [vec![4], x.to_bytes(), y.to_bytes()].concat();
+        assert!(g2_from_bytes(&bytes).is_none());
+    }
+
+    #[test]
+    fn square_roots_square_back() {
+        let a = Fq2 {
+            c0: BigUint::from(5u32),
+            c1: BigUint::from(7u32),
+        };
+        let square = a.mul(&a);
+        let root = square.sqrt().expect("a square");
+        assert!(root.mul(&root) == square);
+        assert!(fq_sqrt(&BigUint::from(4u32)) == Some(BigUint::from(2u32)));
+    }
+}
@@ -0,0 +1,186 @@
+use std::collections::VecDeque;
+
+use polynomen::Poly;
+
+use crate::{
+    crs_player::CrsPlayer, prover::Prover, verificiation::DesignatedVerifier, Proof, ProvingKey,
+    PublicData,
+};
+
+/// What the two parties of the interactive protocol send each other
+pub(crate) enum InteractiveMessage {
+    /// the verifier's encrypted powers of `s` and alpha-shifted powers of `s`
+    Challenge(ProvingKey),
+    /// the prover's answer to the challenge
+    Response(Box<Proof>),
+}
+
+/// How the prover and the verifier reach each other, messages are received in the order they were sent
+pub(crate) trait Channel {
+    fn send(&mut self, message: InteractiveMessage);
+    fn receive(&mut self) -> Option<InteractiveMessage>;
+}
+
+/// both parties run in the same process and share the queue
+pub(crate) struct InMemoryChannel {
+    messages: VecDeque<InteractiveMessage>,
+}
+
+impl InMemoryChannel {
+    pub(crate) fn new() -> Self {
+        Self {
+            messages: VecDeque::new(),
+        }
+    }
+}
+
+impl Channel for InMemoryChannel {
+    fn send(&mut self, message: InteractiveMessage) {
+        self.messages.push_back(message);
+    }
+
+    fn receive(&mut self) -> Option<InteractiveMessage> {
+        return self.messages.pop_front();
+    }
+}
+
+/// The verifier of the interactive protocol of sections 3.1 to 3.5 of the paper.
+///
+/// It samples `s` and `alpha` itself and keeps them, so it checks the response as a
+/// `DesignatedVerifier`. Only this verifier is convinced by the proof.
+pub(crate) struct InteractiveVerifier {
+    target_polynomial: Poly<usize>,
+    s: usize,
+    alpha: usize,
+}
+
+impl InteractiveVerifier {
+    pub(crate) fn new(target_polynomial: Poly<usize>) -> Self {
+        Self {
+            target_polynomial,
+            s: random_secret(),
+            alpha: random_secret(),
+        }
+    }
+
+    /// sends the encrypted powers, generated the same way as a one-player CRS ceremony
+    pub(crate) fn send_challenge(&self, channel: &mut impl Channel) {
+        let public = PublicData {
+            target_polynomial: self.target_polynomial.clone(),
+            crs: CrsPlayer::new(self.s, self.alpha)
+                .start_crs_ceremony()
+                .accumulator,
+        };
+        channel.send(InteractiveMessage::Challenge(ProvingKey::new(&public)));
+    }
+
+    pub(crate) fn check_response(&self, channel: &mut impl Channel) -> bool {
+        let proof = match channel.receive() {
+            Some(InteractiveMessage::Response(proof)) => proof,
+            _ => return false,
+        };
+
+        return DesignatedVerifier::new(&self.target_polynomial, self.s, self.alpha).verify(&proof);
+    }
+}
+
+/// The prover of the interactive protocol, answering with the same proof as `Prover::prove`
+pub(crate) struct InteractiveProver {
+    prover: Prover,
+}
+
+impl InteractiveProver {
+    pub(crate) fn new(p: Poly<usize>) -> Self {
+        Self {
+            prover: Prover::new(p),
+        }
+    }
+
+    /// Answers the challenge. Sends nothing and returns false if the next message is not a
+    /// challenge, or if its powers of s are too few for the prover's polynomial.
+    pub(crate) fn respond(&self, channel: &mut impl Channel) -> bool {
+        let key = match channel.receive() {
+            Some(InteractiveMessage::Challenge(key)) => key,
+            _ => return false,
+        };
+        let proof = match self.prover.prove(&key) {
+            Ok(proof) => proof,
+            Err(_) => return false,
+        };
+        channel.send(InteractiveMessage::Response(Box::new(proof)));
+        return true;
+    }
+}
+
+/// small enough for `s` to the power of `POLYNOMIAL_DEGREE` to fit in a usize, never 0 or 1
+fn random_secret() -> usize {
+    return 2 + rand::random::<usize>() % 4094;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target_polynomial() -> Poly<usize> {
+        return Poly::new_from_coeffs(&[3, 4, 5]);
+    }
+
+    fn run_protocol(verifier: &InteractiveVerifier, prover: &InteractiveProver) -> bool {
+        let mut channel = InMemoryChannel::new();
+        verifier.send_challenge(&mut channel);
+        prover.respond(&mut channel);
+        return verifier.check_response(&mut channel);
+    }
+
+    #[test]
+    fn honest_prover_convinces_the_verifier() {
+        let verifier = InteractiveVerifier::new(target_polynomial());
+        let prover = InteractiveProver::new(target_polynomial() * Poly::new_from_coeffs(&[1, 2]));
+
+        assert!(run_protocol(&verifier, &prover));
+    }
+
+    #[test]
+    fn prover_without_the_target_roots_does_not_convince_the_verifier() {
+        let verifier = InteractiveVerifier::new(target_polynomial());
+        let prover = InteractiveProver::new(Poly::new_from_coeffs(&[1, 2, 3, 4]));
+
+        assert!(!run_protocol(&verifier, &prover));
+    }
+
+    #[test]
+    fn response_to_another_verifiers_challenge_is_rejected() {
+        let verifier = InteractiveVerifier::new(target_polynomial());
+        let other_verifier = InteractiveVerifier::new(target_polynomial());
+        let prover = InteractiveProver::new(target_polynomial() * Poly::new_from_coeffs(&[1, 2]));
+
+        let mut channel = InMemoryChannel::new();
+        other_verifier.send_challenge(&mut channel);
+        assert!(prover.respond(&mut channel));
+
+        assert!(!verifier.check_response(&mut channel));
+    }
+
+    #[test]
+    fn verifier_rejects_a_message_that_is_not_a_response() {
+        let verifier = InteractiveVerifier::new(target_polynomial());
+        let mut channel = InMemoryChannel::new();
+        verifier.send_challenge(&mut channel);
+
+        assert!(!verifier.check_response(&mut channel));
+    }
+
+    #[test]
+    fn prover_refuses_a_message_that_is_not_a_challenge() {
+        let verifier = InteractiveVerifier::new(target_polynomial());
+        let prover = InteractiveProver::new(target_polynomial() * Poly::new_from_coeffs(&[1, 2]));
+        let mut channel = InMemoryChannel::new();
+        assert!(!prover.respond(&mut channel));
+
+        verifier.send_challenge(&mut channel);
+        assert!(prover.respond(&mut channel));
+        // out of order: the next message is the prover's own response, not a new challenge
+        assert!(!prover.respond(&mut channel));
+        assert!(!verifier.check_response(&mut channel));
+    }
+}
@@ -1,16 +1,63 @@
+// explicit `return` and the `CRS` acronym are this project's house style
+#![allow(clippy::needless_return, clippy::upper_case_acronyms)]
+// the binary runs only the paper's walkthrough, every other feature is exercised by its module's
+// tests, where dead code is still reported
+#![cfg_attr(not(test), allow(dead_code))]
+
+#[cfg(test)]
+mod benchmarks;
+mod cheating_prover;
+mod circuit;
 mod crs_player;
 mod cryptography;
+mod groth16_backend;
+mod hash_to_curve;
 mod how_to_use_zksnark_crate;
+mod interactive;
+mod malicious_crs_player;
+mod pairing_engine;
+mod polynomial_commitment;
 mod prover;
+mod qap;
+mod r1cs;
+mod serialization;
+#[cfg(test)]
+mod test_fixtures;
+mod toy_engine;
+mod trace;
+mod transcript;
+mod universal_setup;
 mod verificiation;
 
-use crate::{crs_player::CrsPlayer, prover::Prover};
+use crate::{
+    crs_player::CrsPlayer,
+    pairing_engine::{BnEngine, PairingEngine},
+    prover::Prover,
+    toy_engine::ToyEngine,
+};
 use polynomen::Poly;
-use zksnark::groth16::fr::{G1Local, G2Local};
+use zksnark::groth16::{
+    coefficient_poly::CoefficientPoly,
+    fr::{G1Local, G2Local},
+    FrLocal,
+};
 
 const POLYNOMIAL_DEGREE: u32 = 5;
 
 fn main() {
+    let arguments: Vec<String> = std::env::args().skip(1).collect();
+    match parse_arguments(&arguments) {
+        Ok(Some(format)) => {
+            explain_the_protocol(format);
+            return;
+        }
+        Ok(None) => {}
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(2);
+        }
+    }
+
     let target_polynomial: Poly<usize> = Poly::new_from_coeffs(&[3, 4, 5]);
 
     // Create Prover
@@ -28,63 +75,316 @@ fn main() {
         history: vec![alice.start_crs_ceremony()],
     };
 
-    transcript
-        .history
-        .push(bob.continue_crs_ceremony(&transcript));
+    transcript.history.push(
+        bob.continue_crs_ceremony(&transcript)
+            .expect("honest transcript"),
+    );
 
-    transcript
-        .history
-        .push(charlie.continue_crs_ceremony(&transcript));
+    transcript.history.push(
+        charlie
+            .continue_crs_ceremony(&transcript)
+            .expect("honest transcript"),
+    );
 
     let public = PublicData {
         target_polynomial,
         crs: transcript.history.pop().expect("non-empty").accumulator,
     };
 
+    // Each role keeps only the part of the public data it needs
+
+    let proving_key = ProvingKey::new(&public);
+    let verifying_key = VerifyingKey::new(&public).expect("target degree within the CRS");
+
     // Prover creates a proof using only public data and their secret data in `self`
 
-    let proof = prover.prove(&public);
-    let erroneous_proof = prover.erroneous_prove(&public);
+    let proof = prover.prove(&proving_key).expect("degree within the CRS");
+    let erroneous_proof = prover
+        .erroneous_prove(&proving_key)
+        .expect("degree within the CRS");
 
     // proof is validated using only public data and proof data
 
-    let validation = verificiation::verify(&public, &proof);
+    let validation = verificiation::verify(&verifying_key, &proof);
     println!("Valid proof returns true: {}", validation);
 
-    let validation2 = verificiation::verify(&public, &erroneous_proof);
+    let validation2 = verificiation::verify(&verifying_key, &erroneous_proof);
     println!("Erroneous proof returns false: {}", validation2);
 }
 
-struct Proof {
+/// How `--explain` renders the trace of a run
+#[derive(Debug, PartialEq)]
+enum ExplainFormat {
+    Text,
+    Markdown,
+}
+
+/// `--explain` narrates a run of the protocol as text and `--explain=markdown` as Markdown. Without
+/// arguments the paper's walkthrough runs; any other argument is refused.
+fn parse_arguments(arguments: &[String]) -> Result<Option<ExplainFormat>, String> {
+    return match arguments {
+        [] => Ok(None),
+        [argument] if argument == "--explain" => Ok(Some(ExplainFormat::Text)),
+        [argument] if argument == "--explain=markdown" => Ok(Some(ExplainFormat::Markdown)),
+        _ => Err(format!(
+            "unexpected arguments {:?}, expected none, `--explain` or `--explain=markdown`",
+            arguments
+        )),
+    };
+}
+
+/// Every stage of a ceremony, a proof and its verification on the toy backend, whose values decrypt
+fn explain_the_protocol(format: ExplainFormat) {
+    let target_polynomial: Poly<usize> = Poly::new_from_coeffs(&[3, 4, 5]);
+    let ratio_polynomial: Poly<usize> = Poly::new_from_coeffs(&[6, 7]);
+    let prover = Prover::new(&target_polynomial * &ratio_polynomial);
+
+    let (_, trace) = trace::record(|| {
+        let mut transcript = CrsCeremonyTranscript {
+            history: vec![CrsPlayer::<ToyEngine>::with_engine(12, 13).start_crs_ceremony()],
+        };
+        transcript.history.push(
+            CrsPlayer::with_engine(14, 15)
+                .continue_crs_ceremony(&transcript)
+                .expect("honest transcript"),
+        );
+
+        let public = PublicData {
+            target_polynomial,
+            crs: transcript.history.pop().expect("non-empty").accumulator,
+        };
+        let proof = prover
+            .prove(&ProvingKey::new(&public))
+            .expect("degree within the CRS");
+        return verificiation::verify(
+            &VerifyingKey::new(&public).expect("target degree within the CRS"),
+            &proof,
+        );
+    });
+
+    match format {
+        ExplainFormat::Text => print!("{}", trace.render_text()),
+        ExplainFormat::Markdown => print!("{}", trace.render_markdown()),
+    }
+}
+
+struct Proof<E: PairingEngine = BnEngine> {
+    encrypted1_ratio_poly_at_s: E::G1,
+    encrypted1_secret_poly_at_s: E::G1,
+    encrypted1_alpha_times_secret_poly_at_s: E::G1,
+}
+
+struct PublicData<P = Poly<usize>, E: PairingEngine = BnEngine> {
+    target_polynomial: P,
+    crs: CRS<E>,
+}
+
+/// what `Prover::prove` needs from the `PublicData`
+struct ProvingKey<P = Poly<usize>, E: PairingEngine = BnEngine> {
+    target_polynomial: P,
+    encrypted1_s_powers: Vec<E::G1>,
+    encrypted1_alpha_times_s_powers: Vec<E::G1>,
+}
+
+/// what `verify` needs from the `PublicData`, a constant size whatever the polynomial degree
+struct VerifyingKey<E: PairingEngine = BnEngine> {
+    encrypted2_target_poly_at_s: E::G2,
+    encrypted2_alpha: E::G2,
+    encrypted2_one: E::G2,
+}
+
+/// A polynomial of higher degree than the highest power of s in the CRS. Evaluating it at s would
+/// silently drop its higher coefficients.
+#[derive(Debug, PartialEq)]
+enum DegreeError {
+    /// refused by `Prover::prove`, and by `ComputationProver::prove` for its quotient `h(x)`
+    SecretPolynomialExceedsCrs {
+        polynomial_degree: usize,
+        crs_degree: usize,
+    },
+    /// refused by `VerifyingKey::new`
+    TargetPolynomialExceedsCrs {
+        target_degree: usize,
+        crs_degree: usize,
+    },
+    /// refused by `polynomial_commitment::commit`
+    CommittedPolynomialExceedsCrs {
+        polynomial_degree: usize,
+        crs_degree: usize,
+    },
+    /// a CRS without even the power s^0, on which no polynomial can be evaluated, or a ceremony
+    /// without any CRS
+    EmptyCrs,
+    /// refused by `universal_setup::truncate_ceremony_transcript`, which needs at least degree 1
+    Truncation {
+        requested_degree: usize,
+        crs_degree: usize,
+    },
+    /// refused by the homomorphic evaluations of `cryptography` and `PairingEngine`, which have no
+    /// encrypted power of x to multiply the extra coefficients by
+    MoreCoefficientsThanPowers { coefficients: usize, powers: usize },
+    /// refused by `cryptography::homomorphic*_linear_combination`, which takes one weight per
+    /// encrypted value
+    WeightCountMismatch {
+        weights: usize,
+        encrypted_values: usize,
+    },
+}
+
+/// Why a prover produced no proof
+#[derive(Debug, PartialEq)]
+enum ProofError {
+    /// the prover's polynomial does not fit the CRS
+    Degree(DegreeError),
+    /// the target polynomial leaves a remainder, the prover does not know a multiple of it
+    NotAMultipleOfTarget,
+    /// the computation key needs a variable the witness has no value for, it was made for another QAP
+    WitnessTooShort {
+        variable: usize,
+        witness_length: usize,
+    },
+}
+
+impl From<DegreeError> for ProofError {
+    fn from(error: DegreeError) -> Self {
+        return ProofError::Degree(error);
+    }
+}
+
+/// Proof of a computation, sections 4.4 to 4.9 of the paper: each operand is committed separately,
+/// together with its alpha-shifted copy and a beta-shifted sum tying the variable values together
+struct ComputationProof {
+    encrypted1_left_poly_at_s: G1Local,
+    encrypted2_right_poly_at_s: G2Local,
+    encrypted1_output_poly_at_s: G1Local,
+    encrypted1_alpha_times_left_poly_at_s: G1Local,
+    encrypted2_alpha_times_right_poly_at_s: G2Local,
+    encrypted1_alpha_times_output_poly_at_s: G1Local,
+    encrypted1_variable_consistency_poly_at_s: G1Local,
     encrypted1_ratio_poly_at_s: G1Local,
-    encrypted1_secret_poly_at_s: G1Local,
-    encrypted1_alpha_times_secret_poly_at_s: G1Local,
 }
 
-struct PublicData {
-    target_polynomial: Poly<usize>,
-    crs: CRS,
+impl ComputationProof {
+    /// the points the proof sends, those of G1 then those of G2. Every field is destructured, so a
+    /// new one can't be left out of the proof's size.
+    fn points(&self) -> (Vec<G1Local>, Vec<G2Local>) {
+        let ComputationProof {
+            encrypted1_left_poly_at_s,
+            encrypted2_right_poly_at_s,
+            encrypted1_output_poly_at_s,
+            encrypted1_alpha_times_left_poly_at_s,
+            encrypted2_alpha_times_right_poly_at_s,
+            encrypted1_alpha_times_output_poly_at_s,
+            encrypted1_variable_consistency_poly_at_s,
+            encrypted1_ratio_poly_at_s,
+        } = *self;
+        return (
+            vec![
+                encrypted1_left_poly_at_s,
+                encrypted1_output_poly_at_s,
+                encrypted1_alpha_times_left_poly_at_s,
+                encrypted1_alpha_times_output_poly_at_s,
+                encrypted1_variable_consistency_poly_at_s,
+                encrypted1_ratio_poly_at_s,
+            ],
+            vec![
+                encrypted2_right_poly_at_s,
+                encrypted2_alpha_times_right_poly_at_s,
+            ],
+        );
+    }
 }
 
-struct CRS {
-    // Common Reference String
-    encrypted2_alpha: G2Local,
-    encrypted2_s_powers: Vec<G2Local>,
+/// Each variable polynomial is scaled by its operand's rho, with the output's rho the product of
+/// the other two so that `e(L, R)` and `e(O, g)` can be compared.
+///
+/// Only the private variables are in the proving key, the verifier adds in the public ones.
+struct ComputationProvingKey {
+    target_polynomial: CoefficientPoly<FrLocal>,
+    private_variables: Vec<usize>,
     encrypted1_s_powers: Vec<G1Local>,
-    encrypted1_alpha_times_s_powers: Vec<G1Local>,
+    encrypted1_left_variable_polys_at_s: Vec<G1Local>,
+    encrypted2_right_variable_polys_at_s: Vec<G2Local>,
+    encrypted1_output_variable_polys_at_s: Vec<G1Local>,
+    encrypted1_alpha_times_left_variable_polys_at_s: Vec<G1Local>,
+    encrypted2_alpha_times_right_variable_polys_at_s: Vec<G2Local>,
+    encrypted1_alpha_times_output_variable_polys_at_s: Vec<G1Local>,
+    encrypted1_variable_consistency_polys_at_s: Vec<G1Local>,
 }
 
-struct CrsCeremonyTranscript {
-    history: Vec<CrsCeremonyValues>,
+struct ComputationVerifyingKey {
+    encrypted1_one: G1Local,
+    encrypted2_one: G2Local,
+    encrypted2_alpha_left: G2Local,
+    encrypted1_alpha_right: G1Local,
+    encrypted2_alpha_output: G2Local,
+    encrypted1_beta_times_gamma: G1Local,
+    encrypted2_beta_times_gamma: G2Local,
+    encrypted2_gamma: G2Local,
+    encrypted2_target_poly_at_s: G2Local,
+    // the constant one first, then the public inputs
+    encrypted1_left_public_variable_polys_at_s: Vec<G1Local>,
+    encrypted2_right_public_variable_polys_at_s: Vec<G2Local>,
+    encrypted1_output_public_variable_polys_at_s: Vec<G1Local>,
 }
 
-struct CrsCeremonyValues {
-    accumulator: CRS,
-    step: CrsCeremonyStep,
+struct CRS<E: PairingEngine = BnEngine> {
+    // Common Reference String
+    encrypted2_alpha: E::G2,
+    encrypted2_s_powers: Vec<E::G2>,
+    encrypted1_s_powers: Vec<E::G1>,
+    encrypted1_alpha_times_s_powers: Vec<E::G1>,
 }
 
-struct CrsCeremonyStep {
-    encrypted1_s_powers: Vec<G1Local>,
-    encrypted1_alpha: G1Local,
-    encrypted2_alpha_times_s_powers: Vec<G2Local>,
+struct CrsCeremonyTranscript<E: PairingEngine = BnEngine> {
+    history: Vec<CrsCeremonyValues<E>>,
+}
+
+struct CrsCeremonyValues<E: PairingEngine = BnEngine> {
+    accumulator: CRS<E>,
+    step: CrsCeremonyStep<E>,
+}
+
+struct CrsCeremonyStep<E: PairingEngine = BnEngine> {
+    encrypted1_s_powers: Vec<E::G1>,
+    encrypted1_alpha: E::G1,
+    encrypted2_alpha_times_s_powers: Vec<E::G2>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn arguments(arguments: &[&str]) -> Vec<String> {
+        return arguments
+            .iter()
+            .map(|argument| argument.to_string())
+            .collect();
+    }
+
+    #[test]
+    fn explain_flags_select_the_format() {
+        assert_eq!(parse_arguments(&arguments(&[])), Ok(None));
+        assert_eq!(
+            parse_arguments(&arguments(&["--explain"])),
+            Ok(Some(ExplainFormat::Text))
+        );
+        assert_eq!(
+            parse_arguments(&arguments(&["--explain=markdown"])),
+            Ok(Some(ExplainFormat::Markdown))
+        );
+    }
+
+    #[test]
+    fn other_arguments_are_refused() {
+        for refused in [
+            vec!["--explainer"],
+            vec!["--explain=html"],
+            vec!["--explain=markdownx"],
+            vec!["--verbose"],
+            vec!["--explain", "--explain"],
+        ] {
+            assert!(parse_arguments(&arguments(&refused)).is_err());
+        }
+    }
 }
@@ -0,0 +1,289 @@
+// Ceremony participants that publish values an honest `CrsPlayer` never would, and which error of
+// `validate_ceremony_transcript` catches each of them.
+
+use crate::{
+    pairing_engine::PairingEngine, CrsCeremonyStep, CrsCeremonyTranscript, CrsCeremonyValues, CRS,
+    POLYNOMIAL_DEGREE,
+};
+
+/// A named way of tampering with a ceremony contribution
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum MaliciousBehaviour {
+    /// doubles one power of s in G2 only, so the two groups encrypt different values
+    InconsistentG1AndG2Powers,
+    /// doubles the same power of s in G1, G2 and the alpha-shifted powers, which stay consistent with
+    /// each other but are no longer successive powers of one value
+    NonGeometricPowers,
+    /// contributes s = 0, erasing the secrets of every earlier player
+    ZeroSecret,
+    /// updates the accumulator with its s but publishes the step of s + 1
+    StepDoesNotMatchUpdate,
+    /// contributes s = 1, leaving every power of s unchanged
+    OneSecret,
+    /// sets one power of s in G1 to the point at infinity, and publishes the step of its secrets
+    PointAtInfinity,
+    /// publishes the previous accumulator again, with the step of its secrets
+    ReplayedAccumulator,
+    /// drops the highest power of s in G1
+    TruncatedVectors,
+}
+
+impl MaliciousBehaviour {
+    pub(crate) const ALL: [MaliciousBehaviour; 8] = [
+        MaliciousBehaviour::InconsistentG1AndG2Powers,
+        MaliciousBehaviour::NonGeometricPowers,
+        MaliciousBehaviour::ZeroSecret,
+        MaliciousBehaviour::StepDoesNotMatchUpdate,
+        MaliciousBehaviour::OneSecret,
+        MaliciousBehaviour::PointAtInfinity,
+        MaliciousBehaviour::ReplayedAccumulator,
+        MaliciousBehaviour::TruncatedVectors,
+    ];
+}
+
+/// A participant that contributes its `s` and `alpha` like a `CrsPlayer`, then tampers with the
+/// result following `behaviour`. It skips checking the transcript it continues.
+pub(crate) struct MaliciousCrsPlayer {
+    behaviour: MaliciousBehaviour,
+    s: usize,
+    alpha: usize,
+}
+
+impl MaliciousCrsPlayer {
+    pub(crate) fn new(behaviour: MaliciousBehaviour, s: usize, alpha: usize) -> Self {
+        Self {
+            behaviour,
+            s,
+            alpha,
+        }
+    }
+
+    pub(crate) fn continue_crs_ceremony<E: PairingEngine>(
+        &self,
+        transcript: &CrsCeremonyTranscript<E>,
+    ) -> CrsCeremonyValues<E> {
+        let previous = &transcript.history.last().expect("non-empty").accumulator;
+        let mut values = update_without_checks(previous, self.s, self.alpha);
+
+        match self.behaviour {
+            MaliciousBehaviour::InconsistentG1AndG2Powers => {
+                let accumulator = &mut values.accumulator;
+                accumulator.encrypted2_s_powers[2] =
+                    E::homomorphic2_multiply(&accumulator.encrypted2_s_powers[2], 2);
+            }
+            MaliciousBehaviour::NonGeometricPowers => {
+                let accumulator = &mut values.accumulator;
+                accumulator.encrypted2_s_powers[2] =
+                    E::homomorphic2_multiply(&accumulator.encrypted2_s_powers[2], 2);
+                accumulator.encrypted1_s_powers[2] =
+                    E::homomorphic1_multiply(&accumulator.encrypted1_s_powers[2], 2);
+                accumulator.encrypted1_alpha_times_s_powers[2] =
+                    E::homomorphic1_multiply(&accumulator.encrypted1_alpha_times_s_powers[2], 2);
+            }
+            MaliciousBehaviour::ZeroSecret => {
+                values = update_without_checks(previous, 0, self.alpha);
+            }
+            MaliciousBehaviour::StepDoesNotMatchUpdate => {
+                values.step = update_without_checks(previous, self.s + 1, self.alpha).step;
+            }
+            MaliciousBehaviour::OneSecret => {
+                values = update_without_checks(previous, 1, self.alpha);
+            }
+            MaliciousBehaviour::PointAtInfinity => {
+                values.accumulator.encrypted1_s_powers[3] = E::encrypt1(0);
+            }
+            MaliciousBehaviour::ReplayedAccumulator => {
+                values.accumulator = update_without_checks(previous, 1, 1).accumulator;
+            }
+            MaliciousBehaviour::TruncatedVectors => {
+                values.accumulator.encrypted1_s_powers.pop();
+            }
+        }
+        return values;
+    }
+}
+
+/// `previous` with every power of s multiplied by the same power of `s`, and the shifted ones by
+/// `alpha` too, along with the step stating both values
+fn update_without_checks<E: PairingEngine>(
+    previous: &CRS<E>,
+    s: usize,
+    alpha: usize,
+) -> CrsCeremonyValues<E> {
+    let s_powers: Vec<usize> = (0..POLYNOMIAL_DEGREE + 1)
+        .map(|k| s.checked_pow(k).expect("not to overflow"))
+        .collect();
+
+    return CrsCeremonyValues {
+        accumulator: CRS {
+            encrypted2_alpha: E::homomorphic2_multiply(&previous.encrypted2_alpha, alpha),
+            encrypted2_s_powers: previous
+                .encrypted2_s_powers
+                .iter()
+                .zip(&s_powers)
+                .map(|(encrypted2_power, s_power)| {
+                    E::homomorphic2_multiply(encrypted2_power, *s_power)
+                })
+                .collect(),
+            encrypted1_s_powers: previous
+                .encrypted1_s_powers
+                .iter()
+                .zip(&s_powers)
+                .map(|(encrypted1_power, s_power)| {
+                    E::homomorphic1_multiply(encrypted1_power, *s_power)
+                })
+                .collect(),
+            encrypted1_alpha_times_s_powers: previous
+                .encrypted1_alpha_times_s_powers
+                .iter()
+                .zip(&s_powers)
+                .map(|(encrypted1_power, s_power)| {
+                    E::homomorphic1_multiply(encrypted1_power, alpha * s_power)
+                })
+                .collect(),
+        },
+        step: CrsCeremonyStep {
+            encrypted1_s_powers: s_powers
+                .iter()
+                .map(|s_power| E::encrypt1(*s_power))
+                .collect(),
+            encrypted1_alpha: E::encrypt1(alpha),
+            encrypted2_alpha_times_s_powers: s_powers
+                .iter()
+                .map(|s_power| E::mult_and_encrypt2(*s_power, alpha))
+                .collect(),
+        },
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        crs_player::{validate_ceremony_transcript, CeremonyError, CrsPlayer},
+        toy_engine::ToyEngine,
+    };
+
+    /// an honest first contribution followed by a malicious one
+    fn transcript_with(behaviour: MaliciousBehaviour) -> CrsCeremonyTranscript<ToyEngine> {
+        let mut transcript = CrsCeremonyTranscript {
+            history: vec![CrsPlayer::with_engine(12, 13).start_crs_ceremony()],
+        };
+        let malicious =
+            MaliciousCrsPlayer::new(behaviour, 14, 15).continue_crs_ceremony(&transcript);
+        transcript.history.push(malicious);
+        return transcript;
+    }
+
+    fn rejection_of(behaviour: MaliciousBehaviour) -> Result<(), CeremonyError> {
+        return validate_ceremony_transcript(&transcript_with(behaviour));
+    }
+
+    #[test]
+    fn inconsistent_g1_and_g2_powers_are_rejected() {
+        assert_eq!(
+            rejection_of(MaliciousBehaviour::InconsistentG1AndG2Powers),
+            Err(CeremonyError::G1AndG2PowersMismatch { contribution: 1 })
+        );
+    }
+
+    #[test]
+    fn non_geometric_powers_are_rejected() {
+        assert_eq!(
+            rejection_of(MaliciousBehaviour::NonGeometricPowers),
+            Err(CeremonyError::PowersAreNotGeometric { contribution: 1 })
+        );
+    }
+
+    #[test]
+    fn zero_secret_is_rejected() {
+        assert_eq!(
+            rejection_of(MaliciousBehaviour::ZeroSecret),
+            Err(CeremonyError::DegenerateSecret { contribution: 1 })
+        );
+    }
+
+    #[test]
+    fn secret_of_one_is_rejected() {
+        assert_eq!(
+            rejection_of(MaliciousBehaviour::OneSecret),
+            Err(CeremonyError::DegenerateSecret { contribution: 1 })
+        );
+    }
+
+    #[test]
+    fn point_at_infinity_is_rejected() {
+        assert_eq!(
+            rejection_of(MaliciousBehaviour::PointAtInfinity),
+            Err(CeremonyError::PointAtInfinity { contribution: 1 })
+        );
+    }
+
+    #[test]
+    fn degenerate_alpha_of_the_first_player_is_rejected() {
+        for alpha in [0, 1] {
+            let transcript: CrsCeremonyTranscript<ToyEngine> = CrsCeremonyTranscript {
+                history: vec![CrsPlayer::with_engine(12, alpha).start_crs_ceremony()],
+            };
+            assert_eq!(
+                validate_ceremony_transcript(&transcript),
+                Err(CeremonyError::DegenerateSecret { contribution: 0 })
+            );
+        }
+    }
+
+    #[test]
+    fn step_not_matching_the_update_is_rejected() {
+        assert_eq!(
+            rejection_of(MaliciousBehaviour::StepDoesNotMatchUpdate),
+            Err(CeremonyError::StepDoesNotMatchUpdate { contribution: 1 })
+        );
+    }
+
+    #[test]
+    fn replayed_accumulator_is_rejected() {
+        assert_eq!(
+            rejection_of(MaliciousBehaviour::ReplayedAccumulator),
+            Err(CeremonyError::ReplayedAccumulator { contribution: 1 })
+        );
+    }
+
+    #[test]
+    fn truncated_vectors_are_rejected() {
+        assert_eq!(
+            rejection_of(MaliciousBehaviour::TruncatedVectors),
+            Err(CeremonyError::WrongNumberOfPowers { contribution: 1 })
+        );
+    }
+
+    #[test]
+    fn honest_player_refuses_to_continue_after_a_malicious_one() {
+        let transcript = transcript_with(MaliciousBehaviour::StepDoesNotMatchUpdate);
+        assert!(CrsPlayer::with_engine(16, 17)
+            .continue_crs_ceremony(&transcript)
+            .is_err());
+    }
+
+    #[test]
+    fn honest_ceremony_is_accepted() {
+        let mut transcript: CrsCeremonyTranscript<ToyEngine> = CrsCeremonyTranscript {
+            history: vec![CrsPlayer::with_engine(12, 13).start_crs_ceremony()],
+        };
+        let next = CrsPlayer::with_engine(14, 15)
+            .continue_crs_ceremony(&transcript)
+            .expect("honest transcript");
+        transcript.history.push(next);
+        assert_eq!(validate_ceremony_transcript(&transcript), Ok(()));
+    }
+
+    #[test]
+    fn every_behaviour_is_rejected() {
+        for behaviour in MaliciousBehaviour::ALL {
+            assert!(
+                rejection_of(behaviour).is_err(),
+                "{:?} is accepted",
+                behaviour
+            );
+        }
+    }
+}
@@ -0,0 +1,851 @@
+// The groups and the pairing the protocol is written against, so that `Prover`, `CrsPlayer` and
+// `verify` run unchanged on the zksnark crate's types or directly on the bn crate they wrap.
+
+use std::ops::{Add, Mul, Sub};
+
+use bn::Group;
+use polynomen::Poly;
+use rustc_serialize::hex::ToHex;
+use substrate_bn::Group as _;
+use zksnark::groth16::{
+    fr::{G1Local, G2Local, GtLocal},
+    EllipticEncryptable, FrLocal,
+};
+
+use crate::{cryptography, hash_to_curve, serialization, DegreeError};
+
+/// A pairing-friendly curve: a scalar field, two source groups of that order and a target group.
+///
+/// Encryption is multiplication of a group's generator by a scalar. The provided methods are written
+/// in terms of the required ones, a backend overrides them where it has something faster.
+pub(crate) trait PairingEngine {
+    type Scalar: Copy + Add<Output = Self::Scalar> + Mul<Output = Self::Scalar>;
+    type G1: Copy + PartialEq + Add<Output = Self::G1> + Sub<Output = Self::G1>;
+    type G2: Copy + PartialEq + Add<Output = Self::G2> + Sub<Output = Self::G2>;
+    type Gt: PartialEq;
+
+    fn scalar(n: usize) -> Self::Scalar;
+    fn generator1() -> Self::G1;
+    fn generator2() -> Self::G2;
+    fn multiply1(e_a: Self::G1, b: Self::Scalar) -> Self::G1;
+    fn multiply2(e_a: Self::G2, b: Self::Scalar) -> Self::G2;
+    fn pairing(e_a: Self::G1, e_b: Self::G2) -> Self::Gt;
+    /// the group operation of Gt, written multiplicatively
+    fn gt_multiply(a: Self::Gt, b: Self::Gt) -> Self::Gt;
+    fn gt_one() -> Self::Gt;
+
+    /// how a trace shows a group element, only its group unless the backend can show more
+    fn describe1(_e: &Self::G1) -> String {
+        return "a point of G1".to_string();
+    }
+
+    fn describe2(_e: &Self::G2) -> String {
+        return "a point of G2".to_string();
+    }
+
+    fn encrypt1(n: usize) -> Self::G1 {
+        return Self::multiply1(Self::generator1(), Self::scalar(n));
+    }
+
+    fn encrypt2(n: usize) -> Self::G2 {
+        return Self::multiply2(Self::generator2(), Self::scalar(n));
+    }
+
+    /// `n * m` may not fit a usize, so the product is taken in the scalar field
+    fn mult_and_encrypt1(n: usize, m: usize) -> Self::G1 {
+        return Self::multiply1(Self::generator1(), Self::scalar(n) * Self::scalar(m));
+    }
+
+    fn mult_and_encrypt2(n: usize, m: usize) -> Self::G2 {
+        return Self::multiply2(Self::generator2(), Self::scalar(n) * Self::scalar(m));
+    }
+
+    fn homomorphic1_multiply(e_a: &Self::G1, b: usize) -> Self::G1 {
+        return Self::multiply1(*e_a, Self::scalar(b));
+    }
+
+    fn homomorphic2_multiply(e_a: &Self::G2, b: usize) -> Self::G2 {
+        return Self::multiply2(*e_a, Self::scalar(b));
+    }
+
+    fn negate1(e_a: Self::G1) -> Self::G1 {
+        return Self::encrypt1(0) - e_a;
+    }
+
+    fn multi_scalar_multiply1(bases: &[Self::G1], scalars: &[usize]) -> Self::G1 {
+        assert_eq!(bases.len(), scalars.len(), "one scalar per base");
+        return bases
+            .iter()
+            .zip(scalars)
+            .fold(Self::encrypt1(0), |acc, (base, scalar)| {
+                acc + Self::homomorphic1_multiply(base, *scalar)
+            });
+    }
+
+    fn multi_scalar_multiply2(bases: &[Self::G2], scalars: &[usize]) -> Self::G2 {
+        assert_eq!(bases.len(), scalars.len(), "one scalar per base");
+        return bases
+            .iter()
+            .zip(scalars)
+            .fold(Self::encrypt2(0), |acc, (base, scalar)| {
+                acc + Self::homomorphic2_multiply(base, *scalar)
+            });
+    }
+
+    /// `sum_i scalars[i] * bases[i]` for scalars anywhere in the field, which a backend whose scalars
+    /// have an encoding computes with `cryptography::pippenger_multi_scalar_multiply`
+    fn field_multi_scalar_multiply1(bases: &[Self::G1], scalars: &[Self::Scalar]) -> Self::G1 {
+        assert_eq!(bases.len(), scalars.len(), "one scalar per base");
+        return bases
+            .iter()
+            .zip(scalars)
+            .fold(Self::encrypt1(0), |acc, (base, scalar)| {
+                acc + Self::multiply1(*base, *scalar)
+            });
+    }
+
+    /// refuses a polynomial with more coefficients than there are powers, as
+    /// `cryptography::homomorphic1_eval_polynomial` does
+    fn homomorphic1_eval_polynomial(
+        encrypted_x_powers: &[Self::G1],
+        polynomial: &Poly<usize>,
+    ) -> Result<Self::G1, DegreeError> {
+        let coeffs = polynomial.coeffs();
+        let powers = cryptography::lowest_powers(encrypted_x_powers, coeffs.len())?;
+        return Ok(Self::multi_scalar_multiply1(powers, &coeffs));
+    }
+
+    fn homomorphic2_eval_polynomial(
+        encrypted_x_powers: &[Self::G2],
+        polynomial: &Poly<usize>,
+    ) -> Result<Self::G2, DegreeError> {
+        let coeffs = polynomial.coeffs();
+        let powers = cryptography::lowest_powers(encrypted_x_powers, coeffs.len())?;
+        return Ok(Self::multi_scalar_multiply2(powers, &coeffs));
+    }
+
+    /// uses the wrong value for the third polynomial coefficient, as
+    /// `cryptography::erroneous1_homomorphic_eval_polynomial` does
+    fn erroneous1_homomorphic_eval_polynomial(
+        encrypted_x_powers: &[Self::G1],
+        polynomial: &Poly<usize>,
+    ) -> Result<Self::G1, DegreeError> {
+        let coeffs = polynomial.coeffs();
+        let powers = cryptography::lowest_powers(encrypted_x_powers, coeffs.len())?;
+        return Ok(coeffs.into_iter().zip(powers).enumerate().fold(
+            Self::encrypt1(0),
+            |acc, (index, (coeff, encrypted_s_power))| {
+                if index == 2 {
+                    acc + Self::homomorphic1_multiply(encrypted_s_power, coeff + 1)
+                // this is the error
+                } else {
+                    acc + Self::homomorphic1_multiply(encrypted_s_power, coeff)
+                }
+            },
+        ));
+    }
+
+    /// Checks `e(a_1, b_1) * e(a_2, b_2) * ... == 1`, see `cryptography::pairing_product_is_one`
+    fn pairing_product_is_one(pairs: &[(Self::G1, Self::G2)]) -> bool {
+        let product = pairs.iter().fold(Self::gt_one(), |acc, (e_a, e_b)| {
+            Self::gt_multiply(acc, Self::pairing(*e_a, *e_b))
+        });
+        return product == Self::gt_one();
+    }
+}
+
+/// A backend whose values have a byte encoding, for transcripts and for sizes on the wire.
+///
+/// The zksnark crate keeps the bn values it wraps private, so `ZksnarkEngine` has no encoding. This
+/// is why `BnEngine` is the default engine of the protocol's types.
+pub(crate) trait EncodableEngine: PairingEngine {
+    fn scalar_to_bytes(x: &Self::Scalar) -> Vec<u8>;
+    fn g1_to_bytes(e: &Self::G1) -> Vec<u8>;
+    fn g2_to_bytes(e: &Self::G2) -> Vec<u8>;
+
+    /// the scalar `bytes` reduces to, taken as a big-endian integer
+    fn scalar_from_wide_bytes(bytes: &[u8; 64]) -> Self::Scalar;
+}
+
+/// A backend that can hash to its source groups, for points nobody knows the discrete logarithm of,
+/// such as nothing-up-my-sleeve generators.
+pub(crate) trait HashToCurveEngine: PairingEngine {
+    fn hash_to_g1(domain_separation_tag: &str, message: &[u8]) -> Self::G1;
+    fn hash_to_g2(domain_separation_tag: &str, message: &[u8]) -> Self::G2;
+}
+
+/// enough hex digits to tell elements apart in a trace
+fn describe_encoding(bytes: &[u8]) -> String {
+    return format!("{}..", bytes[..bytes.len().min(8)].to_hex());
+}
+
+/// The zksnark crate's `FrLocal` types, with the fixed-base tables and multi-scalar multiplication of
+/// `cryptography.rs`
+pub(crate) struct ZksnarkEngine;
+
+impl PairingEngine for ZksnarkEngine {
+    type Scalar = FrLocal;
+    type G1 = G1Local;
+    type G2 = G2Local;
+    type Gt = GtLocal;
+
+    fn scalar(n: usize) -> FrLocal {
+        return FrLocal::from(n);
+    }
+
+    fn generator1() -> G1Local {
+        return cryptography::encrypted1_one();
+    }
+
+    fn generator2() -> G2Local {
+        return cryptography::encrypted2_one();
+    }
+
+    fn multiply1(e_a: G1Local, b: FrLocal) -> G1Local {
+        return b.exp_encrypted_g1(e_a);
+    }
+
+    fn multiply2(e_a: G2Local, b: FrLocal) -> G2Local {
+        return b.exp_encrypted_g2(e_a);
+    }
+
+    fn pairing(e_a: G1Local, e_b: G2Local) -> GtLocal {
+        return cryptography::crypto_pairing(e_a, e_b);
+    }
+
+    // GtLocal puts the group multiplication in its Add trait
+    fn gt_multiply(a: GtLocal, b: GtLocal) -> GtLocal {
+        return a + b;
+    }
+
+    fn gt_one() -> GtLocal {
+        return cryptography::crypto_pairing(
+            cryptography::encrypt1(0),
+            cryptography::encrypted2_one(),
+        );
+    }
+
+    fn encrypt1(n: usize) -> G1Local {
+        return cryptography::encrypt1(n);
+    }
+
+    fn encrypt2(n: usize) -> G2Local {
+        return cryptography::encrypt2(n);
+    }
+
+    fn mult_and_encrypt1(n: usize, m: usize) -> G1Local {
+        return cryptography::mult_and_encrypt1(n, m);
+    }
+
+    fn mult_and_encrypt2(n: usize, m: usize) -> G2Local {
+        return cryptography::mult_and_encrypt2(n, m);
+    }
+
+    fn multi_scalar_multiply1(bases: &[G1Local], scalars: &[usize]) -> G1Local {
+        return cryptography::multi_scalar_multiply1(bases, scalars);
+    }
+
+    fn multi_scalar_multiply2(bases: &[G2Local], scalars: &[usize]) -> G2Local {
+        return cryptography::multi_scalar_multiply2(bases, scalars);
+    }
+
+    fn homomorphic1_eval_polynomial(
+        encrypted_x_powers: &[G1Local],
+        polynomial: &Poly<usize>,
+    ) -> Result<G1Local, DegreeError> {
+        return cryptography::homomorphic1_eval_polynomial(encrypted_x_powers, polynomial);
+    }
+
+    fn homomorphic2_eval_polynomial(
+        encrypted_x_powers: &[G2Local],
+        polynomial: &Poly<usize>,
+    ) -> Result<G2Local, DegreeError> {
+        return cryptography::homomorphic2_eval_polynomial(encrypted_x_powers, polynomial);
+    }
+
+    fn erroneous1_homomorphic_eval_polynomial(
+        encrypted_x_powers: &[G1Local],
+        polynomial: &Poly<usize>,
+    ) -> Result<G1Local, DegreeError> {
+        return cryptography::erroneous1_homomorphic_eval_polynomial(
+            encrypted_x_powers,
+            polynomial,
+        );
+    }
+
+    fn pairing_product_is_one(pairs: &[(G1Local, G2Local)]) -> bool {
+        return cryptography::pairing_product_is_one(pairs);
+    }
+}
+
+/// The bn crate's types used directly, with its standard generators and the multi-scalar
+/// multiplication of `cryptography.rs`
+pub(crate) struct BnEngine;
+
+impl PairingEngine for BnEngine {
+    type Scalar = bn::Fr;
+    type G1 = bn::G1;
+    type G2 = bn::G2;
+    type Gt = bn::Gt;
+
+    fn scalar(n: usize) -> bn::Fr {
+        return bn::Fr::from_str(&n.to_string()).expect("a usize is below the group order");
+    }
+
+    fn generator1() -> bn::G1 {
+        return bn::G1::one();
+    }
+
+    fn generator2() -> bn::G2 {
+        return bn::G2::one();
+    }
+
+    fn multiply1(e_a: bn::G1, b: bn::Fr) -> bn::G1 {
+        return e_a * b;
+    }
+
+    fn multiply2(e_a: bn::G2, b: bn::Fr) -> bn::G2 {
+        return e_a * b;
+    }
+
+    fn pairing(e_a: bn::G1, e_b: bn::G2) -> bn::Gt {
+        return bn::pairing(e_a, e_b);
+    }
+
+    fn gt_multiply(a: bn::Gt, b: bn::Gt) -> bn::Gt {
+        return a * b;
+    }
+
+    fn gt_one() -> bn::Gt {
+        return bn::Gt::one();
+    }
+
+    fn multi_scalar_multiply1(bases: &[bn::G1], scalars: &[usize]) -> bn::G1 {
+        return cryptography::pippenger_multi_scalar_multiply(
+            bases,
+            &cryptography::usize_encodings(scalars),
+            bn::G1::zero(),
+        );
+    }
+
+    fn multi_scalar_multiply2(bases: &[bn::G2], scalars: &[usize]) -> bn::G2 {
+        return cryptography::pippenger_multi_scalar_multiply(
+            bases,
+            &cryptography::usize_encodings(scalars),
+            bn::G2::zero(),
+        );
+    }
+
+    fn field_multi_scalar_multiply1(bases: &[bn::G1], scalars: &[bn::Fr]) -> bn::G1 {
+        let encodings: Vec<Vec<u8>> = scalars.iter().map(Self::scalar_to_bytes).collect();
+        return cryptography::pippenger_multi_scalar_multiply(bases, &encodings, bn::G1::zero());
+    }
+
+    /// the start of its encoding
+    fn describe1(e: &bn::G1) -> String {
+        return describe_encoding(&Self::g1_to_bytes(e));
+    }
+
+    fn describe2(e: &bn::G2) -> String {
+        return describe_encoding(&Self::g2_to_bytes(e));
+    }
+}
+
+impl EncodableEngine for BnEngine {
+    fn scalar_to_bytes(x: &bn::Fr) -> Vec<u8> {
+        return serialization::to_bytes(x);
+    }
+
+    fn g1_to_bytes(e: &bn::G1) -> Vec<u8> {
+        return serialization::to_bytes(e);
+    }
+
+    fn g2_to_bytes(e: &bn::G2) -> Vec<u8> {
+        return serialization::to_bytes(e);
+    }
+
+    fn scalar_from_wide_bytes(bytes: &[u8; 64]) -> bn::Fr {
+        return bn::Fr::interpret(bytes);
+    }
+}
+
+impl HashToCurveEngine for BnEngine {
+    fn hash_to_g1(domain_separation_tag: &str, message: &[u8]) -> bn::G1 {
+        return hash_to_curve::hash_to_g1(domain_separation_tag, message);
+    }
+
+    fn hash_to_g2(domain_separation_tag: &str, message: &[u8]) -> bn::G2 {
+        return hash_to_curve::hash_to_g2(domain_separation_tag, message);
+    }
+}
+
+/// The substrate-bn fork of the bn crate, whose Miller loops share one final exponentiation in a
+/// pairing product, with the multi-scalar multiplication of `cryptography.rs`
+pub(crate) struct SubstrateBnEngine;
+
+impl PairingEngine for SubstrateBnEngine {
+    type Scalar = substrate_bn::Fr;
+    type G1 = substrate_bn::G1;
+    type G2 = substrate_bn::G2;
+    type Gt = substrate_bn::Gt;
+
+    fn scalar(n: usize) -> substrate_bn::Fr {
+        return substrate_bn::Fr::from_str(&n.to_string())
+            .expect("a usize is below the group order");
+    }
+
+    fn generator1() -> substrate_bn::G1 {
+        return substrate_bn::G1::one();
+    }
+
+    fn generator2() -> substrate_bn::G2 {
+        return substrate_bn::G2::one();
+    }
+
+    fn multiply1(e_a: substrate_bn::G1, b: substrate_bn::Fr) -> substrate_bn::G1 {
+        return e_a * b;
+    }
+
+    fn multiply2(e_a: substrate_bn::G2, b: substrate_bn::Fr) -> substrate_bn::G2 {
+        return e_a * b;
+    }
+
+    fn pairing(e_a: substrate_bn::G1, e_b: substrate_bn::G2) -> substrate_bn::Gt {
+        return substrate_bn::pairing(e_a, e_b);
+    }
+
+    fn gt_multiply(a: substrate_bn::Gt, b: substrate_bn::Gt) -> substrate_bn::Gt {
+        return a * b;
+    }
+
+    fn gt_one() -> substrate_bn::Gt {
+        return substrate_bn::Gt::one();
+    }
+
+    fn multi_scalar_multiply1(bases: &[substrate_bn::G1], scalars: &[usize]) -> substrate_bn::G1 {
+        return cryptography::pippenger_multi_scalar_multiply(
+            bases,
+            &cryptography::usize_encodings(scalars),
+            substrate_bn::G1::zero(),
+        );
+    }
+
+    fn multi_scalar_multiply2(bases: &[substrate_bn::G2], scalars: &[usize]) -> substrate_bn::G2 {
+        return cryptography::pippenger_multi_scalar_multiply(
+            bases,
+            &cryptography::usize_encodings(scalars),
+            substrate_bn::G2::zero(),
+        );
+    }
+
+    fn field_multi_scalar_multiply1(
+        bases: &[substrate_bn::G1],
+        scalars: &[substrate_bn::Fr],
+    ) -> substrate_bn::G1 {
+        let encodings: Vec<[u8; 32]> = scalars
+            .iter()
+            .map(|scalar| {
+                // `Fr::to_big_endian` writes the Montgomery form, the canonical value is its `U256`
+                let mut encoding = [0; 32];
+                scalar
+                    .into_u256()
+                    .to_big_endian(&mut encoding)
+                    .expect("a scalar takes 32 bytes");
+                return encoding;
+            })
+            .collect();
+        return cryptography::pippenger_multi_scalar_multiply(
+            bases,
+            &encodings,
+            substrate_bn::G1::zero(),
+        );
+    }
+
+    /// one Miller loop per pair and a single final exponentiation for the whole product
+    fn pairing_product_is_one(pairs: &[(substrate_bn::G1, substrate_bn::G2)]) -> bool {
+        return substrate_bn::pairing_batch(pairs) == substrate_bn::Gt::one();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cheating_prover::{CheatingProver, CheatingStrategy},
+        crs_player::{validate_ceremony_transcript, CeremonyError, CrsPlayer},
+        malicious_crs_player::{MaliciousBehaviour, MaliciousCrsPlayer},
+        polynomial_commitment,
+        prover::Prover,
+        toy_engine::ToyEngine,
+        verificiation::verify,
+        CrsCeremonyTranscript, ProofError, ProvingKey, PublicData, VerifyingKey, POLYNOMIAL_DEGREE,
+    };
+    use proptest::prelude::*;
+
+    /// runs a generic test once per backend
+    macro_rules! test_with_every_engine {
+        ($($test:ident),*) => {
+            $(
+                mod $test {
+                    use super::*;
+
+                    #[test]
+                    fn zksnark() {
+                        $test::<ZksnarkEngine>();
+                    }
+
+                    #[test]
+                    fn bn() {
+                        $test::<BnEngine>();
+                    }
+
+                    #[test]
+                    fn substrate_bn() {
+                        $test::<SubstrateBnEngine>();
+                    }
+                }
+            )*
+        };
+    }
+
+    test_with_every_engine!(
+        encryption_is_homomorphic_and_bilinear,
+        honest_proof_from_a_ceremony_verifies,
+        proof_without_the_target_roots_does_not_verify,
+        ceremony_rejects_degenerate_contributions,
+        polynomial_commitment_opens_at_a_point,
+        multi_scalar_multiplication_matches_the_sum_of_products
+    );
+
+    /// runs a generic property on every backend with few cases, as each case costs real pairings,
+    /// and on the toy backend with as many cases as the other properties
+    macro_rules! proptest_with_every_engine {
+        ($($property:ident($($argument:ident in $strategy:expr),*);)*) => {
+            $(
+                mod $property {
+                    use super::*;
+
+                    proptest! {
+                        #![proptest_config(ProptestConfig::with_cases(4))]
+
+                        #[test]
+                        fn zksnark($($argument in $strategy),*) {
+                            $property::<ZksnarkEngine>($($argument),*)?;
+                        }
+
+                        #[test]
+                        fn bn($($argument in $strategy),*) {
+                            $property::<BnEngine>($($argument),*)?;
+                        }
+
+                        #[test]
+                        fn substrate_bn($($argument in $strategy),*) {
+                            $property::<SubstrateBnEngine>($($argument),*)?;
+                        }
+                    }
+
+                    proptest! {
+                        #![proptest_config(ProptestConfig::with_cases(32))]
+
+                        #[test]
+                        fn toy($($argument in $strategy),*) {
+                            $property::<ToyEngine>($($argument),*)?;
+                        }
+                    }
+                }
+            )*
+        };
+    }
+
+    // A target of degree 1 to 3 times a ratio of degree 1 or 2 stays within the CRS degree, and has
+    // the `s^2` term `erroneous_prove` corrupts.
+    proptest_with_every_engine! {
+        honest_proofs_always_verify(
+            target in polynomial(1..=3),
+            ratio in polynomial(1..=2),
+            s in 2..1000usize,
+            alpha in 2..1000usize
+        );
+        erroneous_proofs_never_verify(
+            target in polynomial(1..=3),
+            ratio in polynomial(1..=2),
+            s in 2..1000usize,
+            alpha in 2..1000usize
+        );
+        homomorphic_evaluation_matches_encrypting_the_value(
+            coeffs in prop::collection::vec(0..1000usize, 1..=(POLYNOMIAL_DEGREE as usize + 1)),
+            s in 0..500usize
+        );
+    }
+
+    fn encryption_is_homomorphic_and_bilinear<E: PairingEngine>() {
+        assert!(E::encrypt1(3) + E::encrypt1(4) == E::encrypt1(7));
+        assert!(E::homomorphic2_multiply(&E::encrypt2(3), 4) == E::mult_and_encrypt2(3, 4));
+        assert!(E::negate1(E::encrypt1(5)) + E::encrypt1(5) == E::encrypt1(0));
+
+        assert!(E::pairing_product_is_one(&[
+            (E::encrypt1(6), E::encrypt2(7)),
+            (E::negate1(E::encrypt1(42)), E::generator2()),
+        ]));
+        assert!(!E::pairing_product_is_one(&[
+            (E::encrypt1(6), E::encrypt2(7)),
+            (E::negate1(E::encrypt1(43)), E::generator2()),
+        ]));
+    }
+
+    fn multi_scalar_multiplication_matches_the_sum_of_products<E: PairingEngine>() {
+        // enough terms for a window wider than the smallest, and scalars of every size
+        let scalars: Vec<usize> = (0..40).map(|k| usize::MAX / (k + 1) * (k % 3)).collect();
+        let bases1: Vec<E::G1> = (0..40).map(|k| E::encrypt1(k + 2)).collect();
+        let bases2: Vec<E::G2> = (0..40).map(|k| E::encrypt2(k + 2)).collect();
+
+        let sum1 = bases1
+            .iter()
+            .zip(&scalars)
+            .fold(E::encrypt1(0), |acc, (base, scalar)| {
+                acc + E::homomorphic1_multiply(base, *scalar)
+            });
+        let sum2 = bases2
+            .iter()
+            .zip(&scalars)
+            .fold(E::encrypt2(0), |acc, (base, scalar)| {
+                acc + E::homomorphic2_multiply(base, *scalar)
+            });
+
+        assert!(E::multi_scalar_multiply1(&bases1, &scalars) == sum1);
+        assert!(E::multi_scalar_multiply2(&bases2, &scalars) == sum2);
+        assert!(E::multi_scalar_multiply1(&[], &[]) == E::encrypt1(0));
+
+        // products of usizes wrap around the group order, so these take every bit of a scalar
+        let field_scalars: Vec<E::Scalar> = scalars
+            .iter()
+            .enumerate()
+            .map(|(k, scalar)| {
+                E::scalar(*scalar)
+                    * E::scalar(usize::MAX - k)
+                    * E::scalar(usize::MAX / 3)
+                    * E::scalar(usize::MAX)
+            })
+            .collect();
+        let field_sum1 = bases1
+            .iter()
+            .zip(&field_scalars)
+            .fold(E::encrypt1(0), |acc, (base, scalar)| {
+                acc + E::multiply1(*base, *scalar)
+            });
+        assert!(E::field_multi_scalar_multiply1(&bases1, &field_scalars) == field_sum1);
+    }
+
+    fn public_data<E: PairingEngine>() -> PublicData<Poly<usize>, E> {
+        let mut transcript: CrsCeremonyTranscript<E> = CrsCeremonyTranscript {
+            history: vec![CrsPlayer::<E>::with_engine(12, 13).start_crs_ceremony()],
+        };
+        transcript.history.push(
+            CrsPlayer::<E>::with_engine(14, 15)
+                .continue_crs_ceremony(&transcript)
+                .expect("honest transcript"),
+        );
+
+        return PublicData {
+            target_polynomial: Poly::new_from_coeffs(&[3, 4, 5]),
+            crs: transcript.history.pop().expect("non-empty").accumulator,
+        };
+    }
+
+    fn honest_proof_from_a_ceremony_verifies<E: PairingEngine>() {
+        let public = public_data::<E>();
+        let prover = Prover::new(&public.target_polynomial * &Poly::new_from_coeffs(&[6, 7]));
+
+        let proof = prover
+            .prove(&ProvingKey::new(&public))
+            .expect("degree within the CRS");
+        assert!(verify(
+            &VerifyingKey::new(&public).expect("target degree within the CRS"),
+            &proof
+        ));
+    }
+
+    fn proof_without_the_target_roots_does_not_verify<E: PairingEngine>() {
+        let public = public_data::<E>();
+        let proving_key = ProvingKey::new(&public);
+        assert_eq!(
+            Prover::new(Poly::new_from_coeffs(&[1, 2, 3, 4]))
+                .prove(&proving_key)
+                .err(),
+            Some(ProofError::NotAMultipleOfTarget)
+        );
+
+        // a prover that evaluates such a polynomial all the same is caught by the verifier
+        let proof = CheatingProver::new(CheatingStrategy::NotDivisibleByTarget)
+            .prove(&proving_key)
+            .expect("degree within the CRS");
+        assert!(!verify(
+            &VerifyingKey::new(&public).expect("target degree within the CRS"),
+            &proof
+        ));
+    }
+
+    fn ceremony_rejects_degenerate_contributions<E: PairingEngine>() {
+        let mut transcript: CrsCeremonyTranscript<E> = CrsCeremonyTranscript {
+            history: vec![CrsPlayer::<E>::with_engine(12, 13).start_crs_ceremony()],
+        };
+
+        let zero_secret = MaliciousCrsPlayer::new(MaliciousBehaviour::ZeroSecret, 14, 15)
+            .continue_crs_ceremony(&transcript);
+        transcript.history.push(zero_secret);
+        assert_eq!(
+            validate_ceremony_transcript(&transcript),
+            Err(CeremonyError::DegenerateSecret { contribution: 1 })
+        );
+        transcript.history.pop();
+
+        // a power wiped out by multiplying it by 0 is the same point as the encryption of 0
+        let mut wiped_power = CrsPlayer::<E>::with_engine(14, 15)
+            .continue_crs_ceremony(&transcript)
+            .expect("honest transcript");
+        wiped_power.accumulator.encrypted2_s_powers[2] =
+            E::homomorphic2_multiply(&wiped_power.accumulator.encrypted2_s_powers[2], 0);
+        transcript.history.push(wiped_power);
+        assert_eq!(
+            validate_ceremony_transcript(&transcript),
+            Err(CeremonyError::PointAtInfinity { contribution: 1 })
+        );
+    }
+
+    fn polynomial_commitment_opens_at_a_point<E: PairingEngine>() {
+        let crs = public_data::<E>().crs;
+        let polynomial: Poly<usize> = Poly::new_from_coeffs(&[3, 4, 5]);
+        let commitment =
+            polynomial_commitment::commit(&crs, &polynomial).expect("degree within the CRS");
+
+        let mut opening = polynomial_commitment::open(&crs, &polynomial, E::scalar(2))
+            .expect("degree within the CRS");
+        assert!(polynomial_commitment::verify_opening(
+            &crs,
+            &commitment,
+            &opening
+        ));
+        opening.value = opening.value + E::scalar(1);
+        assert!(!polynomial_commitment::verify_opening(
+            &crs,
+            &commitment,
+            &opening
+        ));
+    }
+
+    #[test]
+    fn bn_encodings_have_fixed_sizes() {
+        for n in [1, 12345] {
+            assert_eq!(BnEngine::scalar_to_bytes(&BnEngine::scalar(n)).len(), 32);
+            assert_eq!(
+                BnEngine::g1_to_bytes(&BnEngine::encrypt1(n)).len(),
+                1 + 2 * 32
+            );
+            assert_eq!(
+                BnEngine::g2_to_bytes(&BnEngine::encrypt2(n)).len(),
+                1 + 2 * 64
+            );
+        }
+    }
+
+    #[test]
+    fn wide_bytes_are_reduced_modulo_the_group_order() {
+        let mut bytes = [0; 64];
+        bytes[63] = 7;
+        assert!(BnEngine::scalar_from_wide_bytes(&bytes) == BnEngine::scalar(7));
+    }
+
+    #[test]
+    fn hashed_points_do_not_pair_like_the_generators() {
+        let point1 = BnEngine::hash_to_g1("learning-zksnarks test G1", b"block 800000");
+        let point2 = BnEngine::hash_to_g2("learning-zksnarks test G2", b"block 800000");
+
+        // their discrete logarithms are unrelated, so e(H1, g2) and e(g1, H2) differ
+        assert!(!BnEngine::pairing_product_is_one(&[
+            (point1, BnEngine::generator2()),
+            (BnEngine::negate1(BnEngine::generator1()), point2),
+        ]));
+    }
+
+    #[test]
+    fn bn_engine_uses_the_standard_generator() {
+        assert_eq!(
+            BnEngine::g1_to_bytes(&BnEngine::encrypt1(1)).to_hex(),
+            format!("04{:064x}{:064x}", 1, 2)
+        );
+    }
+
+    /// a polynomial of degree `degrees` with coefficients in 1..100, so its leading one is never 0
+    fn polynomial(degrees: std::ops::RangeInclusive<usize>) -> impl Strategy<Value = Poly<usize>> {
+        return prop::collection::vec(1..100usize, (degrees.start() + 1)..=(degrees.end() + 1))
+            .prop_map(|coeffs| Poly::new_from_coeffs(&coeffs));
+    }
+
+    fn public_data_for<E: PairingEngine>(
+        target_polynomial: Poly<usize>,
+        s: usize,
+        alpha: usize,
+    ) -> PublicData<Poly<usize>, E> {
+        return PublicData {
+            target_polynomial,
+            crs: CrsPlayer::with_engine(s, alpha)
+                .start_crs_ceremony()
+                .accumulator,
+        };
+    }
+
+    fn honest_proofs_always_verify<E: PairingEngine>(
+        target: Poly<usize>,
+        ratio: Poly<usize>,
+        s: usize,
+        alpha: usize,
+    ) -> Result<(), TestCaseError> {
+        let public = public_data_for::<E>(target, s, alpha);
+        let prover = Prover::new(&public.target_polynomial * &ratio);
+
+        let proof = prover
+            .prove(&ProvingKey::new(&public))
+            .expect("degree within the CRS");
+        prop_assert!(verify(
+            &VerifyingKey::new(&public).expect("target degree within the CRS"),
+            &proof
+        ));
+        return Ok(());
+    }
+
+    fn erroneous_proofs_never_verify<E: PairingEngine>(
+        target: Poly<usize>,
+        ratio: Poly<usize>,
+        s: usize,
+        alpha: usize,
+    ) -> Result<(), TestCaseError> {
+        let public = public_data_for::<E>(target, s, alpha);
+        let prover = Prover::new(&public.target_polynomial * &ratio);
+
+        let proof = prover
+            .erroneous_prove(&ProvingKey::new(&public))
+            .expect("degree within the CRS");
+        prop_assert!(!verify(
+            &VerifyingKey::new(&public).expect("target degree within the CRS"),
+            &proof
+        ));
+        return Ok(());
+    }
+
+    fn homomorphic_evaluation_matches_encrypting_the_value<E: PairingEngine>(
+        coeffs: Vec<usize>,
+        s: usize,
+    ) -> Result<(), TestCaseError> {
+        let encrypted1_s_powers: Vec<E::G1> = (0..POLYNOMIAL_DEGREE + 1)
+            .map(|k| E::encrypt1(s.pow(k)))
+            .collect();
+        let p_at_s: usize = coeffs.iter().rev().fold(0, |acc, coeff| acc * s + coeff);
+
+        prop_assert!(
+            E::homomorphic1_eval_polynomial(&encrypted1_s_powers, &Poly::new_from_coeffs(&coeffs))
+                .ok()
+                == Some(E::encrypt1(p_at_s))
+        );
+        return Ok(());
+    }
+}
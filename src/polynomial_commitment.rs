@@ -0,0 +1,383 @@
+// KZG polynomial commitments on the powers of s of a ceremony's CRS.
+//
+// A commitment to p(x) is `g^p(s)`. Opening it at a point z reveals `y = p(z)` together with a
+// commitment to the quotient `q(x) = (p(x) - y) / (x - z)`, which exists only if y is the true
+// value. The verifier checks `p(s) - y = q(s) (s - z)` with one pairing per side, so the same
+// ceremony output serves for evaluation proofs as well as for the protocol of section 3.
+//
+// Any `ProvablePolynomial` of the engine can be committed, so the polynomials of a QAP as well as the
+// small integer polynomials of the paper's examples.
+
+use crate::{
+    cryptography,
+    pairing_engine::{BnEngine, EncodableEngine, PairingEngine},
+    prover::ProvablePolynomial,
+    transcript::Transcript,
+    DegreeError, CRS,
+};
+
+/// The value of a committed polynomial at `point`, with the commitment to the quotient as witness
+pub(crate) struct Opening<E: PairingEngine = BnEngine> {
+    pub(crate) point: E::Scalar,
+    pub(crate) value: E::Scalar,
+    pub(crate) witness: E::G1,
+}
+
+/// The values of several committed polynomials at the same `point`, with a single witness: the
+/// quotients combined with the successive powers of a challenge `gamma`, see `batch_challenge`
+pub(crate) struct BatchOpening<E: PairingEngine = BnEngine> {
+    pub(crate) point: E::Scalar,
+    pub(crate) values: Vec<E::Scalar>,
+    pub(crate) witness: E::G1,
+}
+
+/// `g^p(s)`, refusing a polynomial the CRS has too few powers of s for
+pub(crate) fn commit<E: PairingEngine, P: ProvablePolynomial<E>>(
+    crs: &CRS<E>,
+    polynomial: &P,
+) -> Result<E::G1, DegreeError> {
+    check_degree(crs, polynomial)?;
+    return polynomial.homomorphic1_eval(&crs.encrypted1_s_powers);
+}
+
+/// Everything is computed in the scalar field, so any point can be opened, whatever the value.
+pub(crate) fn open<E: PairingEngine, P: ProvablePolynomial<E>>(
+    crs: &CRS<E>,
+    polynomial: &P,
+    point: E::Scalar,
+) -> Result<Opening<E>, DegreeError> {
+    // the quotient is one degree lower, so it alone could fit a polynomial that does not
+    check_degree(crs, polynomial)?;
+    let (quotient, value) = divide_by_linear_factor(polynomial, point);
+    return Ok(Opening {
+        point,
+        value,
+        witness: commit_to_coefficients(crs, &quotient)?,
+    });
+}
+
+/// Opens every polynomial at `point`, combining the witnesses with the powers of the challenge
+/// `batch_challenge` derives from the commitments, the point and the values.
+pub(crate) fn batch_open<E: EncodableEngine, P: ProvablePolynomial<E>>(
+    crs: &CRS<E>,
+    polynomials: &[P],
+    point: E::Scalar,
+) -> Result<BatchOpening<E>, DegreeError> {
+    let mut commitments = vec![];
+    let mut openings = vec![];
+    for polynomial in polynomials {
+        commitments.push(commit(crs, polynomial)?);
+        openings.push(open(crs, polynomial, point)?);
+    }
+
+    let values: Vec<E::Scalar> = openings.iter().map(|opening| opening.value).collect();
+    let gamma_powers = powers::<E>(
+        batch_challenge::<E>(&commitments, point, &values),
+        openings.len(),
+    );
+    let witness = openings
+        .iter()
+        .zip(gamma_powers)
+        .fold(E::encrypt1(0), |acc, (opening, gamma_power)| {
+            acc + E::multiply1(opening.witness, gamma_power)
+        });
+    return Ok(BatchOpening {
+        point,
+        values,
+        witness,
+    });
+}
+
+/// `e(C - g^y, g) == e(W, g^s - g^z)`
+pub(crate) fn verify_opening<E: PairingEngine>(
+    crs: &CRS<E>,
+    commitment: &E::G1,
+    opening: &Opening<E>,
+) -> bool {
+    return quotient_relation_holds(
+        crs,
+        *commitment - E::multiply1(E::generator1(), opening.value),
+        opening.witness,
+        opening.point,
+    );
+}
+
+/// the check of `verify_opening` on the commitments and values combined with the powers of the
+/// challenge `batch_open` used
+pub(crate) fn verify_batch_opening<E: EncodableEngine>(
+    crs: &CRS<E>,
+    commitments: &[E::G1],
+    opening: &BatchOpening<E>,
+) -> bool {
+    if commitments.len() != opening.values.len() {
+        return false;
+    }
+
+    let gamma_powers = powers::<E>(
+        batch_challenge::<E>(commitments, opening.point, &opening.values),
+        commitments.len(),
+    );
+    let combined_commitment_minus_values = commitments
+        .iter()
+        .zip(&opening.values)
+        .zip(gamma_powers)
+        .fold(E::encrypt1(0), |acc, ((commitment, value), gamma_power)| {
+            acc + E::multiply1(
+                *commitment - E::multiply1(E::generator1(), *value),
+                gamma_power,
+            )
+        });
+    return quotient_relation_holds(
+        crs,
+        combined_commitment_minus_values,
+        opening.witness,
+        opening.point,
+    );
+}
+
+/// Fiat-Shamir for the challenge a verifier would pick once it has the commitments: a prover who
+/// could choose it would pick false values whose errors cancel out.
+fn batch_challenge<E: EncodableEngine>(
+    commitments: &[E::G1],
+    point: E::Scalar,
+    values: &[E::Scalar],
+) -> E::Scalar {
+    let mut transcript = Transcript::<E>::new("learning-zksnarks KZG batch opening");
+    transcript.append_g1_vector("commitments", commitments);
+    transcript.append_field_element("point", &point);
+    let value_bytes: Vec<u8> = values.iter().flat_map(E::scalar_to_bytes).collect();
+    transcript.append_bytes("values", &value_bytes);
+    return transcript.challenge_field_element("gamma");
+}
+
+fn check_degree<E: PairingEngine, P: ProvablePolynomial<E>>(
+    crs: &CRS<E>,
+    polynomial: &P,
+) -> Result<(), DegreeError> {
+    let polynomial_degree = polynomial.degree();
+    let crs_degree = crs
+        .encrypted1_s_powers
+        .len()
+        .checked_sub(1)
+        .ok_or(DegreeError::EmptyCrs)?;
+    if polynomial_degree > crs_degree {
+        return Err(DegreeError::CommittedPolynomialExceedsCrs {
+            polynomial_degree,
+            crs_degree,
+        });
+    }
+    return Ok(());
+}
+
+/// `g^q(s)` for the coefficients of `q`, refusing more coefficients than the CRS has powers of s
+fn commit_to_coefficients<E: PairingEngine>(
+    crs: &CRS<E>,
+    coefficients: &[E::Scalar],
+) -> Result<E::G1, DegreeError> {
+    let powers = cryptography::lowest_powers(&crs.encrypted1_s_powers, coefficients.len())?;
+    return Ok(E::field_multi_scalar_multiply1(powers, coefficients));
+}
+
+/// whether `encrypted1_difference` is `g^(q(s) (s - point))` for the `q(s)` hidden in `witness`, and
+/// false for a CRS without `g^s`
+fn quotient_relation_holds<E: PairingEngine>(
+    crs: &CRS<E>,
+    encrypted1_difference: E::G1,
+    witness: E::G1,
+    point: E::Scalar,
+) -> bool {
+    let Some(encrypted2_s) = crs.encrypted2_s_powers.get(1) else {
+        return false;
+    };
+    let encrypted2_s_minus_point = *encrypted2_s - E::multiply2(E::generator2(), point);
+    return E::pairing_product_is_one(&[
+        (encrypted1_difference, E::generator2()),
+        (E::negate1(witness), encrypted2_s_minus_point),
+    ]);
+}
+
+/// the coefficients of `(p(x) - p(point)) / (x - point)` and `p(point)`, by synthetic division in the
+/// scalar field
+fn divide_by_linear_factor<E: PairingEngine, P: ProvablePolynomial<E>>(
+    polynomial: &P,
+    point: E::Scalar,
+) -> (Vec<E::Scalar>, E::Scalar) {
+    let mut coeffs = polynomial.coefficients();
+    if coeffs.is_empty() {
+        coeffs.push(E::scalar(0));
+    }
+    let mut quotient = vec![E::scalar(0); coeffs.len().max(2) - 1];
+    let mut carry = E::scalar(0);
+    for k in (1..coeffs.len()).rev() {
+        carry = coeffs[k] + point * carry;
+        quotient[k - 1] = carry;
+    }
+    return (quotient, coeffs[0] + point * carry);
+}
+
+/// `1, gamma, gamma^2, ...`, `count` of them
+fn powers<E: PairingEngine>(gamma: E::Scalar, count: usize) -> Vec<E::Scalar> {
+    return std::iter::successors(Some(E::scalar(1)), |power| Some(*power * gamma))
+        .take(count)
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use polynomen::Poly;
+    use zksnark::groth16::{coefficient_poly::CoefficientPoly, FrLocal};
+
+    use super::*;
+    use crate::{
+        circuit::cubic_circuit,
+        crs_player::CrsPlayer,
+        pairing_engine::ZksnarkEngine,
+        toy_engine::{decrypt, ToyEngine},
+    };
+
+    fn crs() -> CRS<ToyEngine> {
+        return CrsPlayer::with_engine(12, 13)
+            .start_crs_ceremony()
+            .accumulator;
+    }
+
+    fn polynomial() -> Poly<usize> {
+        return Poly::new_from_coeffs(&[3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn commitment_is_the_polynomial_at_s() {
+        let commitment = commit(&crs(), &polynomial()).expect("degree within the CRS");
+        assert_eq!(decrypt(commitment), 3 + 4 * 12 + 5 * 144 + 6 * 1728);
+    }
+
+    #[test]
+    fn opening_at_a_point_verifies() {
+        let crs = crs();
+        let commitment = commit(&crs, &polynomial()).expect("degree within the CRS");
+
+        let opening =
+            open(&crs, &polynomial(), ToyEngine::scalar(7)).expect("degree within the CRS");
+        assert_eq!(
+            opening.value,
+            ToyEngine::scalar(3 + 4 * 7 + 5 * 49 + 6 * 343)
+        );
+        assert!(verify_opening(&crs, &commitment, &opening));
+    }
+
+    #[test]
+    fn opening_with_a_wrong_value_fails() {
+        let crs = crs();
+        let commitment = commit(&crs, &polynomial()).expect("degree within the CRS");
+
+        let mut opening =
+            open(&crs, &polynomial(), ToyEngine::scalar(7)).expect("degree within the CRS");
+        opening.value = opening.value + ToyEngine::scalar(1);
+        assert!(!verify_opening(&crs, &commitment, &opening));
+    }
+
+    #[test]
+    fn opening_of_another_polynomial_fails() {
+        let crs = crs();
+        let commitment = commit(&crs, &polynomial()).expect("degree within the CRS");
+
+        let other: Poly<usize> = Poly::new_from_coeffs(&[3, 4, 5, 7]);
+        let opening = open(&crs, &other, ToyEngine::scalar(7)).expect("degree within the CRS");
+        assert!(!verify_opening(&crs, &commitment, &opening));
+    }
+
+    #[test]
+    fn opening_at_a_point_beyond_a_usize_verifies() {
+        let crs = CrsPlayer::<BnEngine>::with_engine(12, 13)
+            .start_crs_ceremony()
+            .accumulator;
+        let commitment = commit(&crs, &polynomial()).expect("degree within the CRS");
+        let point = BnEngine::scalar(usize::MAX) * BnEngine::scalar(usize::MAX);
+
+        let mut opening = open(&crs, &polynomial(), point).expect("degree within the CRS");
+        assert!(verify_opening(&crs, &commitment, &opening));
+        opening.value = opening.value + BnEngine::scalar(1);
+        assert!(!verify_opening(&crs, &commitment, &opening));
+    }
+
+    #[test]
+    fn batch_opening_verifies_and_catches_a_wrong_value() {
+        let crs = crs();
+        let polynomials = vec![
+            polynomial(),
+            Poly::new_from_coeffs(&[1]),
+            Poly::new_from_coeffs(&[9, 8, 7, 6, 5, 4]),
+        ];
+        let commitments: Vec<_> = polynomials
+            .iter()
+            .map(|polynomial| commit(&crs, polynomial).expect("degree within the CRS"))
+            .collect();
+
+        let mut opening =
+            batch_open(&crs, &polynomials, ToyEngine::scalar(7)).expect("degree within the CRS");
+        assert_eq!(opening.values[1], ToyEngine::scalar(1));
+        assert!(verify_batch_opening(&crs, &commitments, &opening));
+
+        opening.values[2] = opening.values[2] + ToyEngine::scalar(1);
+        assert!(!verify_batch_opening(&crs, &commitments, &opening));
+    }
+
+    #[test]
+    fn large_batch_opening_verifies() {
+        let crs = crs();
+        // enough polynomials that the powers of gamma run far past a usize
+        let polynomials: Vec<Poly<usize>> = (0..64)
+            .map(|k| Poly::new_from_coeffs(&[k, k + 1, 2 * k, 3]))
+            .collect();
+        let commitments: Vec<_> = polynomials
+            .iter()
+            .map(|polynomial| commit(&crs, polynomial).expect("degree within the CRS"))
+            .collect();
+
+        let opening =
+            batch_open(&crs, &polynomials, ToyEngine::scalar(5)).expect("degree within the CRS");
+        assert!(verify_batch_opening(&crs, &commitments, &opening));
+        assert!(!verify_batch_opening(
+            &crs,
+            &commitments[1..],
+            &BatchOpening {
+                point: opening.point,
+                values: opening.values[1..].to_vec(),
+                witness: opening.witness,
+            }
+        ));
+    }
+
+    #[test]
+    fn polynomial_beyond_the_crs_degree_is_refused() {
+        let too_high: Poly<usize> = Poly::new_from_coeffs(&[1, 1, 1, 1, 1, 1, 1]);
+        assert!(open(&crs(), &too_high, ToyEngine::scalar(7)).is_err());
+        assert_eq!(
+            commit(&crs(), &too_high).err(),
+            Some(DegreeError::CommittedPolynomialExceedsCrs {
+                polynomial_degree: 6,
+                crs_degree: 5
+            })
+        );
+    }
+
+    #[test]
+    fn polynomial_of_a_qap_is_committed_and_opened() {
+        let circuit = cubic_circuit();
+        let qap = circuit.compile();
+        let polynomial: CoefficientPoly<FrLocal> = qap.prover_polynomial(
+            &circuit
+                .compute_witness(&[("x", 3)])
+                .expect("x is the circuit's input"),
+        );
+        let crs = CrsPlayer::<ZksnarkEngine>::with_engine(12, 13)
+            .start_crs_ceremony()
+            .accumulator;
+        let commitment = commit(&crs, &polynomial).expect("degree within the CRS");
+
+        let mut opening = open(&crs, &polynomial, FrLocal::from(7)).expect("degree within the CRS");
+        assert!(verify_opening(&crs, &commitment, &opening));
+        opening.value = opening.value + FrLocal::from(1);
+        assert!(!verify_opening(&crs, &commitment, &opening));
+    }
+}
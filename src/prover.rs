@@ -1,86 +1,424 @@
 use polynomen::Poly;
+use zksnark::{
+    field::{FieldIdentity, Polynomial},
+    groth16::{
+        coefficient_poly::CoefficientPoly,
+        fr::{G1Local, G2Local},
+        FrLocal,
+    },
+};
 
 use crate::{
     cryptography::{
-        erroneous1_homomorphic_eval_polynomial, homomorphic1_eval_polynomial, homomorphic1_multiply,
+        homomorphic1_eval_field_polynomial, homomorphic1_linear_combination,
+        homomorphic2_eval_field_polynomial, homomorphic2_linear_combination,
     },
-    Proof, PublicData,
+    pairing_engine::{BnEngine, PairingEngine, ZksnarkEngine},
+    qap::QuadraticArithmeticProgram,
+    trace, ComputationProof, ComputationProvingKey, DegreeError, Proof, ProofError, ProvingKey,
+    PublicData, CRS,
 };
 
-pub(crate) struct Prover {
-    my_secret_polynomial: Poly<usize>,
+/// A polynomial that can be evaluated at the secret `s` using only the encrypted powers of `s`.
+///
+/// Implemented for the small integer polynomials of the paper's examples, on every pairing engine, and
+/// for polynomials over the zksnark crate's scalar field, such as the ones a QAP produces.
+pub(crate) trait ProvablePolynomial<E: PairingEngine = BnEngine>: Clone {
+    /// refuses a polynomial with more coefficients than there are powers
+    fn homomorphic1_eval(&self, encrypted_x_powers: &[E::G1]) -> Result<E::G1, DegreeError>;
+    fn homomorphic2_eval(&self, encrypted_x_powers: &[E::G2]) -> Result<E::G2, DegreeError>;
+    /// `None` when `divisor` leaves a remainder, i.e. it is not a factor of the polynomial
+    fn exact_divide(&self, divisor: &Self) -> Option<Self>;
+    /// the degree of the zero polynomial is 0
+    fn degree(&self) -> usize;
+    /// the coefficients from the constant one up, in the engine's scalar field
+    fn coefficients(&self) -> Vec<E::Scalar>;
+    /// how a trace shows the polynomial
+    fn describe(&self) -> String;
 }
 
-impl Prover {
-    pub fn new(p: Poly<usize>) -> Self {
+impl<E: PairingEngine> ProvablePolynomial<E> for Poly<usize> {
+    fn homomorphic1_eval(&self, encrypted_x_powers: &[E::G1]) -> Result<E::G1, DegreeError> {
+        return E::homomorphic1_eval_polynomial(encrypted_x_powers, self);
+    }
+
+    fn homomorphic2_eval(&self, encrypted_x_powers: &[E::G2]) -> Result<E::G2, DegreeError> {
+        return E::homomorphic2_eval_polynomial(encrypted_x_powers, self);
+    }
+
+    fn exact_divide(&self, divisor: &Self) -> Option<Self> {
+        return exact_divide_integer_polynomial(self, divisor);
+    }
+
+    fn degree(&self) -> usize {
+        return Poly::degree(self).unwrap_or(0);
+    }
+
+    fn coefficients(&self) -> Vec<E::Scalar> {
+        return self.coeffs().into_iter().map(E::scalar).collect();
+    }
+
+    fn describe(&self) -> String {
+        return format!("{}", self);
+    }
+}
+
+impl ProvablePolynomial<ZksnarkEngine> for CoefficientPoly<FrLocal> {
+    fn homomorphic1_eval(&self, encrypted_x_powers: &[G1Local]) -> Result<G1Local, DegreeError> {
+        return homomorphic1_eval_field_polynomial(encrypted_x_powers, self);
+    }
+
+    fn homomorphic2_eval(&self, encrypted_x_powers: &[G2Local]) -> Result<G2Local, DegreeError> {
+        return homomorphic2_eval_field_polynomial(encrypted_x_powers, self);
+    }
+
+    fn exact_divide(&self, divisor: &Self) -> Option<Self> {
+        let quotient = self.clone() / divisor.clone();
+        let remainder = self.clone() - quotient.clone() * divisor.clone();
+        if remainder
+            .iter()
+            .any(|coefficient| *coefficient != FrLocal::zero())
+        {
+            return None;
+        }
+        return Some(quotient);
+    }
+
+    fn degree(&self) -> usize {
+        return Polynomial::degree(self);
+    }
+
+    fn coefficients(&self) -> Vec<FrLocal> {
+        return self.to_vec();
+    }
+
+    /// the coefficients are too large to read
+    fn describe(&self) -> String {
+        return format!("degree {} over the scalar field", Polynomial::degree(self));
+    }
+}
+
+pub(crate) struct Prover<P = Poly<usize>> {
+    my_secret_polynomial: P,
+}
+
+impl<P: Clone> Prover<P> {
+    pub fn new(p: P) -> Self {
         Self {
             my_secret_polynomial: p,
         }
     }
 
-    pub(crate) fn prove(&self, public: &PublicData) -> Proof {
-        let random_entropy = 7 as usize;
+    /// Refuses a polynomial of higher degree than the CRS, which the proof could not evaluate at s
+    pub(crate) fn prove<E: PairingEngine>(
+        &self,
+        key: &ProvingKey<P, E>,
+    ) -> Result<Proof<E>, ProofError>
+    where
+        P: ProvablePolynomial<E>,
+    {
+        let polynomial_degree = self.my_secret_polynomial.degree();
+        let crs_degree = key
+            .encrypted1_s_powers
+            .len()
+            .checked_sub(1)
+            .ok_or(DegreeError::EmptyCrs)?;
+        if polynomial_degree > crs_degree {
+            return Err(ProofError::Degree(
+                DegreeError::SecretPolynomialExceedsCrs {
+                    polynomial_degree,
+                    crs_degree,
+                },
+            ));
+        }
 
-        let h: Poly<usize> =
-            exact_divide_integer_polynomial(&self.my_secret_polynomial, &public.target_polynomial);
-        let crs = &public.crs;
+        let random_entropy: usize = 7;
+
+        let h: P = self
+            .my_secret_polynomial
+            .exact_divide(&key.target_polynomial)
+            .ok_or(ProofError::NotAMultipleOfTarget)?;
+        trace::note(
+            "3.2",
+            "Prover: quotient",
+            "The prover divides its polynomial by the target polynomial t(x). Only a polynomial with all \
+             the roots of t(x) leaves no remainder.",
+            || vec![("p(x)", self.my_secret_polynomial.describe()), ("h(x)", h.describe())],
+        );
+
+        let encrypted1_secret_poly_at_s = self
+            .my_secret_polynomial
+            .homomorphic1_eval(&key.encrypted1_s_powers)?;
+        let encrypted1_ratio_poly_at_s = h.homomorphic1_eval(&key.encrypted1_s_powers)?;
+        trace::note(
+            "3.3",
+            "Prover: obscure evaluation",
+            "Both polynomials are evaluated at the secret s using only the encrypted powers of s from \
+             the CRS, so the prover never learns s.",
+            || {
+                vec![
+                    ("g^p(s)", E::describe1(&encrypted1_secret_poly_at_s)),
+                    ("g^h(s)", E::describe1(&encrypted1_ratio_poly_at_s)),
+                ]
+            },
+        );
+
+        let encrypted1_alpha_times_secret_poly_at_s = self
+            .my_secret_polynomial
+            .homomorphic1_eval(&key.encrypted1_alpha_times_s_powers)?;
+        trace::note(
+            "3.4",
+            "Prover: alpha-shifted evaluation",
+            "The same polynomial is evaluated on the alpha-shifted powers. Without alpha, only a \
+             combination of the given powers can produce a matching pair.",
+            || {
+                vec![(
+                    "g^(alpha p(s))",
+                    E::describe1(&encrypted1_alpha_times_secret_poly_at_s),
+                )]
+            },
+        );
 
-        return Proof {
-            encrypted1_secret_poly_at_s: homomorphic1_multiply(
-                &homomorphic1_eval_polynomial(&crs.encrypted1_s_powers, &self.my_secret_polynomial),
+        let proof = Proof {
+            encrypted1_secret_poly_at_s: E::homomorphic1_multiply(
+                &encrypted1_secret_poly_at_s,
                 random_entropy,
             ),
-            encrypted1_ratio_poly_at_s: homomorphic1_multiply(
-                &homomorphic1_eval_polynomial(&crs.encrypted1_s_powers, &h),
+            encrypted1_ratio_poly_at_s: E::homomorphic1_multiply(
+                &encrypted1_ratio_poly_at_s,
                 random_entropy,
             ),
-            encrypted1_alpha_times_secret_poly_at_s: homomorphic1_multiply(
-                &homomorphic1_eval_polynomial(
-                    &crs.encrypted1_alpha_times_s_powers,
-                    &self.my_secret_polynomial,
-                ),
+            encrypted1_alpha_times_secret_poly_at_s: E::homomorphic1_multiply(
+                &encrypted1_alpha_times_secret_poly_at_s,
                 random_entropy,
             ),
         };
+        trace::note(
+            "3.5",
+            "Prover: zero-knowledge shift",
+            "Every value is raised to the same random delta, which keeps the verifier's equations \
+             true while hiding p(s).",
+            || {
+                vec![
+                    ("delta", random_entropy.to_string()),
+                    (
+                        "g^(delta p(s))",
+                        E::describe1(&proof.encrypted1_secret_poly_at_s),
+                    ),
+                    (
+                        "g^(delta h(s))",
+                        E::describe1(&proof.encrypted1_ratio_poly_at_s),
+                    ),
+                    (
+                        "g^(delta alpha p(s))",
+                        E::describe1(&proof.encrypted1_alpha_times_secret_poly_at_s),
+                    ),
+                ]
+            },
+        );
+        return Ok(proof);
     }
+}
 
-    pub(crate) fn erroneous_prove(&self, public: &PublicData) -> Proof {
-        let random_entropy = 7 as usize;
+impl Prover {
+    /// like `prove`, refuses a polynomial the target does not divide or the CRS can't evaluate
+    pub(crate) fn erroneous_prove<E: PairingEngine>(
+        &self,
+        key: &ProvingKey<Poly<usize>, E>,
+    ) -> Result<Proof<E>, ProofError> {
+        let random_entropy: usize = 7;
 
         let h: Poly<usize> =
-            exact_divide_integer_polynomial(&self.my_secret_polynomial, &public.target_polynomial);
-        let crs = &public.crs;
+            exact_divide_integer_polynomial(&self.my_secret_polynomial, &key.target_polynomial)
+                .ok_or(ProofError::NotAMultipleOfTarget)?;
 
-        return Proof {
-            encrypted1_secret_poly_at_s: homomorphic1_multiply(
-                &homomorphic1_eval_polynomial(&crs.encrypted1_s_powers, &self.my_secret_polynomial),
+        return Ok(Proof {
+            encrypted1_secret_poly_at_s: E::homomorphic1_multiply(
+                &E::homomorphic1_eval_polynomial(
+                    &key.encrypted1_s_powers,
+                    &self.my_secret_polynomial,
+                )?,
                 random_entropy,
             ),
-            encrypted1_ratio_poly_at_s: homomorphic1_multiply(
-                &homomorphic1_eval_polynomial(&crs.encrypted1_s_powers, &h),
+            encrypted1_ratio_poly_at_s: E::homomorphic1_multiply(
+                &E::homomorphic1_eval_polynomial(&key.encrypted1_s_powers, &h)?,
                 random_entropy,
             ),
-            encrypted1_alpha_times_secret_poly_at_s: homomorphic1_multiply(
-                &erroneous1_homomorphic_eval_polynomial(
-                    &crs.encrypted1_alpha_times_s_powers,
+            encrypted1_alpha_times_secret_poly_at_s: E::homomorphic1_multiply(
+                &E::erroneous1_homomorphic_eval_polynomial(
+                    &key.encrypted1_alpha_times_s_powers,
                     &self.my_secret_polynomial,
-                ),
+                )?,
                 random_entropy,
             ),
-        };
+        });
+    }
+}
+
+impl<P: ProvablePolynomial<E>, E: PairingEngine> ProvingKey<P, E> {
+    pub(crate) fn new(public: &PublicData<P, E>) -> Self {
+        return Self::for_target(public.target_polynomial.clone(), &public.crs);
+    }
+
+    /// the key for one statement of a CRS that several share, see `UniversalReferenceString`
+    pub(crate) fn for_target(target_polynomial: P, crs: &CRS<E>) -> Self {
+        Self {
+            target_polynomial,
+            encrypted1_s_powers: crs.encrypted1_s_powers.clone(),
+            encrypted1_alpha_times_s_powers: crs.encrypted1_alpha_times_s_powers.clone(),
+        }
     }
 }
 
-fn exact_divide_integer_polynomial(p_top: &Poly<usize>, p_bottom: &Poly<usize>) -> Poly<usize> {
-    return cast_polynomial_to_usize(
-        cast_polynomial_to_f64(p_top) / cast_polynomial_to_f64(p_bottom),
-    );
+/// Proves knowledge of a witness satisfying every operation of a QAP, sections 4.4 to 4.9 of the paper
+pub(crate) struct ComputationProver {
+    my_secret_witness: Vec<FrLocal>,
+    my_secret_polynomial: CoefficientPoly<FrLocal>,
+}
+
+impl ComputationProver {
+    pub fn new(qap: &QuadraticArithmeticProgram, witness: Vec<FrLocal>) -> Self {
+        Self {
+            my_secret_polynomial: qap.prover_polynomial(&witness),
+            my_secret_witness: witness,
+        }
+    }
+
+    /// Refuses a witness that does not satisfy the QAP, whose polynomial the target does not divide,
+    /// and a key made for another QAP, with variables the witness lacks or too few powers of s
+    pub(crate) fn prove(
+        &self,
+        key: &ComputationProvingKey,
+    ) -> Result<ComputationProof, ProofError> {
+        let witness_length = self.my_secret_witness.len();
+        if let Some(variable) = key
+            .private_variables
+            .iter()
+            .find(|variable| **variable >= witness_length)
+        {
+            return Err(ProofError::WitnessTooShort {
+                variable: *variable,
+                witness_length,
+            });
+        }
+        let witness: Vec<FrLocal> = key
+            .private_variables
+            .iter()
+            .map(|variable| self.my_secret_witness[*variable])
+            .collect();
+        let h = self
+            .my_secret_polynomial
+            .exact_divide(&key.target_polynomial)
+            .ok_or(ProofError::NotAMultipleOfTarget)?;
+
+        let polynomial_degree = Polynomial::degree(&h);
+        let crs_degree = key
+            .encrypted1_s_powers
+            .len()
+            .checked_sub(1)
+            .ok_or(DegreeError::EmptyCrs)?;
+        if polynomial_degree > crs_degree {
+            return Err(ProofError::Degree(
+                DegreeError::SecretPolynomialExceedsCrs {
+                    polynomial_degree,
+                    crs_degree,
+                },
+            ));
+        }
+
+        return Ok(ComputationProof {
+            encrypted1_left_poly_at_s: homomorphic1_linear_combination(
+                &key.encrypted1_left_variable_polys_at_s,
+                &witness,
+            )?,
+            encrypted2_right_poly_at_s: homomorphic2_linear_combination(
+                &key.encrypted2_right_variable_polys_at_s,
+                &witness,
+            )?,
+            encrypted1_output_poly_at_s: homomorphic1_linear_combination(
+                &key.encrypted1_output_variable_polys_at_s,
+                &witness,
+            )?,
+            encrypted1_alpha_times_left_poly_at_s: homomorphic1_linear_combination(
+                &key.encrypted1_alpha_times_left_variable_polys_at_s,
+                &witness,
+            )?,
+            encrypted2_alpha_times_right_poly_at_s: homomorphic2_linear_combination(
+                &key.encrypted2_alpha_times_right_variable_polys_at_s,
+                &witness,
+            )?,
+            encrypted1_alpha_times_output_poly_at_s: homomorphic1_linear_combination(
+                &key.encrypted1_alpha_times_output_variable_polys_at_s,
+                &witness,
+            )?,
+            encrypted1_variable_consistency_poly_at_s: homomorphic1_linear_combination(
+                &key.encrypted1_variable_consistency_polys_at_s,
+                &witness,
+            )?,
+            encrypted1_ratio_poly_at_s: h.homomorphic1_eval(&key.encrypted1_s_powers)?,
+        });
+    }
 }
 
-fn cast_polynomial_to_f64(p: &Poly<usize>) -> Poly<f64> {
-    return Poly::new_from_coeffs_iter(p.coeffs().iter().map(|x| *x as f64));
+/// `p_top / p_bottom` by long division over the integers. `None` if it leaves a remainder, needs a
+/// fractional or negative coefficient, or overflows.
+fn exact_divide_integer_polynomial(
+    p_top: &Poly<usize>,
+    p_bottom: &Poly<usize>,
+) -> Option<Poly<usize>> {
+    let divisor: Vec<i128> = p_bottom.coeffs().iter().map(|x| *x as i128).collect();
+    let divisor_degree = divisor.len() - 1;
+    let divisor_leading_coeff = divisor[divisor_degree];
+    if divisor_leading_coeff == 0 {
+        return None;
+    }
+
+    let mut remainder: Vec<i128> = p_top.coeffs().iter().map(|x| *x as i128).collect();
+    let mut quotient: Vec<i128> = vec![0; (remainder.len() + 1).saturating_sub(divisor.len())];
+    for k in (0..quotient.len()).rev() {
+        let leading_coeff = remainder[k + divisor_degree];
+        if leading_coeff % divisor_leading_coeff != 0 {
+            return None;
+        }
+        quotient[k] = leading_coeff / divisor_leading_coeff;
+        for (j, divisor_coeff) in divisor.iter().enumerate() {
+            remainder[k + j] =
+                remainder[k + j].checked_sub(quotient[k].checked_mul(*divisor_coeff)?)?;
+        }
+    }
+
+    if remainder.iter().any(|x| *x != 0) || quotient.iter().any(|x| *x < 0) {
+        return None;
+    }
+    if quotient.is_empty() {
+        quotient.push(0);
+    }
+    return Some(Poly::new_from_coeffs_iter(
+        quotient.into_iter().map(|x| x as usize),
+    ));
 }
 
-fn cast_polynomial_to_usize(p: Poly<f64>) -> Poly<usize> {
-    return Poly::new_from_coeffs_iter(p.coeffs().iter().map(|x| *x as usize));
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integer_division_recovers_the_exact_quotient() {
+        let target = Poly::new_from_coeffs(&[3, 4, 5]);
+        let ratio = Poly::new_from_coeffs(&[6, 7, 1_000_000_007]);
+        assert!(exact_divide_integer_polynomial(&(&target * &ratio), &target) == Some(ratio));
+    }
+
+    #[test]
+    fn integer_division_refuses_a_remainder_or_a_negative_quotient() {
+        let target = Poly::new_from_coeffs(&[3, 4, 5]);
+        let with_remainder =
+            &target * &Poly::new_from_coeffs(&[6, 7]) + Poly::new_from_coeffs(&[1]);
+        assert!(exact_divide_integer_polynomial(&with_remainder, &target).is_none());
+
+        // x^3 + 1 = (x + 1)(x^2 - x + 1)
+        let cube_plus_one = Poly::new_from_coeffs(&[1, 0, 0, 1]);
+        let x_plus_one = Poly::new_from_coeffs(&[1, 1]);
+        assert!(exact_divide_integer_polynomial(&cube_plus_one, &x_plus_one).is_none());
+    }
 }
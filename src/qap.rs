@@ -0,0 +1,185 @@
+use zksnark::{
+    field::FieldIdentity,
+    groth16::{
+        coefficient_poly::{root_poly, CoefficientPoly},
+        FrLocal,
+    },
+};
+
+/// `(variable index, coefficient)` pairs, where variable 0 is the constant one
+pub(crate) type LinearCombination = Vec<(usize, FrLocal)>;
+
+/// One `left operand * right operand = output` operation, as in section 4.3 of the paper, where each
+/// side is a linear combination of variables so that additions and constants come for free (4.6, 4.7)
+pub(crate) struct Operation {
+    pub(crate) left: LinearCombination,
+    pub(crate) right: LinearCombination,
+    pub(crate) output: LinearCombination,
+}
+
+/// Quadratic Arithmetic Program: the variable polynomials of section 4.4, which at `x = i` give the
+/// coefficient of the variable in the i-th operation, and the target polynomial `t(x) = (x-1)...(x-n)`
+pub(crate) struct QuadraticArithmeticProgram {
+    left_variable_polynomials: Vec<CoefficientPoly<FrLocal>>,
+    right_variable_polynomials: Vec<CoefficientPoly<FrLocal>>,
+    output_variable_polynomials: Vec<CoefficientPoly<FrLocal>>,
+    target_polynomial: CoefficientPoly<FrLocal>,
+}
+
+impl QuadraticArithmeticProgram {
+    pub(crate) fn new(number_of_variables: usize, operations: &[Operation]) -> Self {
+        let roots: Vec<FrLocal> = (1..operations.len() + 1).map(FrLocal::from).collect();
+
+        let interpolate_operand = |operand: fn(&Operation) -> &LinearCombination| {
+            (0..number_of_variables)
+                .map(|variable| {
+                    let values_at_roots: Vec<FrLocal> = operations
+                        .iter()
+                        .map(|operation| coefficient_of(operand(operation), variable))
+                        .collect();
+                    interpolate(&roots, &values_at_roots)
+                })
+                .collect()
+        };
+
+        return Self {
+            left_variable_polynomials: interpolate_operand(|operation| &operation.left),
+            right_variable_polynomials: interpolate_operand(|operation| &operation.right),
+            output_variable_polynomials: interpolate_operand(|operation| &operation.output),
+            target_polynomial: root_poly(roots.into_iter()),
+        };
+    }
+
+    pub(crate) fn number_of_variables(&self) -> usize {
+        return self.left_variable_polynomials.len();
+    }
+
+    pub(crate) fn number_of_operations(&self) -> usize {
+        return self.target_polynomial.len() - 1;
+    }
+
+    pub(crate) fn target_polynomial(&self) -> &CoefficientPoly<FrLocal> {
+        return &self.target_polynomial;
+    }
+
+    pub(crate) fn left_variable_polynomials(&self) -> &[CoefficientPoly<FrLocal>] {
+        return &self.left_variable_polynomials;
+    }
+
+    pub(crate) fn right_variable_polynomials(&self) -> &[CoefficientPoly<FrLocal>] {
+        return &self.right_variable_polynomials;
+    }
+
+    pub(crate) fn output_variable_polynomials(&self) -> &[CoefficientPoly<FrLocal>] {
+        return &self.output_variable_polynomials;
+    }
+
+    /// `L(x) * R(x) - O(x)`, where `L(x)` is the sum of the left variable polynomials weighted by the
+    /// witness values. It is divisible by the target polynomial exactly when the witness satisfies
+    /// every operation.
+    pub(crate) fn prover_polynomial(&self, witness: &[FrLocal]) -> CoefficientPoly<FrLocal> {
+        assert_eq!(
+            witness.len(),
+            self.number_of_variables(),
+            "one witness value per variable"
+        );
+
+        let left = weighted_sum(&self.left_variable_polynomials, witness);
+        let right = weighted_sum(&self.right_variable_polynomials, witness);
+        let output = weighted_sum(&self.output_variable_polynomials, witness);
+
+        return left * right - output;
+    }
+}
+
+fn coefficient_of(linear_combination: &LinearCombination, variable: usize) -> FrLocal {
+    return linear_combination
+        .iter()
+        .filter(|(index, _)| *index == variable)
+        .fold(FrLocal::zero(), |acc, (_, coefficient)| acc + *coefficient);
+}
+
+/// Lagrange interpolation through `(roots[i], values[i])`
+fn interpolate(roots: &[FrLocal], values: &[FrLocal]) -> CoefficientPoly<FrLocal> {
+    if values.iter().all(|value| *value == FrLocal::zero()) {
+        return CoefficientPoly::from(vec![FrLocal::zero()]);
+    }
+    return CoefficientPoly::from((
+        roots.iter().copied(),
+        roots.iter().copied().zip(values.iter().copied()),
+    ));
+}
+
+pub(crate) fn weighted_sum(
+    polynomials: &[CoefficientPoly<FrLocal>],
+    weights: &[FrLocal],
+) -> CoefficientPoly<FrLocal> {
+    return polynomials
+        .iter()
+        .zip(weights)
+        .map(|(polynomial, weight)| polynomial.clone() * *weight)
+        .sum();
+}
+
+#[cfg(test)]
+mod tests {
+    use zksnark::field::Polynomial;
+
+    use super::*;
+
+    /// Petkus section 4.4: `a * b = r` with a single variable per operand
+    fn single_multiplication() -> QuadraticArithmeticProgram {
+        let one = FrLocal::one();
+        return QuadraticArithmeticProgram::new(
+            4,
+            &[Operation {
+                left: vec![(1, one)],
+                right: vec![(2, one)],
+                output: vec![(3, one)],
+            }],
+        );
+    }
+
+    #[test]
+    fn variable_polynomials_take_operation_coefficients_at_the_roots() {
+        let one = FrLocal::one();
+        let qap = QuadraticArithmeticProgram::new(
+            3,
+            &[
+                Operation {
+                    left: vec![(1, one), (2, one)],
+                    right: vec![(0, one)],
+                    output: vec![(2, FrLocal::from(3))],
+                },
+                Operation {
+                    left: vec![(1, FrLocal::from(5))],
+                    right: vec![(1, one)],
+                    output: vec![(2, one)],
+                },
+            ],
+        );
+
+        let x1 = FrLocal::from(1);
+        let x2 = FrLocal::from(2);
+        assert!(qap.left_variable_polynomials()[1].evaluate(x1) == one);
+        assert!(qap.left_variable_polynomials()[1].evaluate(x2) == FrLocal::from(5));
+        assert!(qap.left_variable_polynomials()[2].evaluate(x2) == FrLocal::zero());
+        assert!(qap.right_variable_polynomials()[0].evaluate(x1) == one);
+        assert!(qap.output_variable_polynomials()[2].evaluate(x1) == FrLocal::from(3));
+        assert!(qap.target_polynomial().evaluate(x1) == FrLocal::zero());
+        assert!(qap.target_polynomial().evaluate(x2) == FrLocal::zero());
+        assert!(qap.target_polynomial().evaluate(FrLocal::from(3)) != FrLocal::zero());
+    }
+
+    #[test]
+    fn prover_polynomial_is_divisible_only_for_satisfying_witness() {
+        let qap = single_multiplication();
+        let witness = |r: usize| -> Vec<FrLocal> {
+            vec![1, 3, 2, r].into_iter().map(FrLocal::from).collect()
+        };
+
+        // the target polynomial has the single root 1
+        assert!(qap.prover_polynomial(&witness(6)).evaluate(FrLocal::one()) == FrLocal::zero());
+        assert!(qap.prover_polynomial(&witness(7)).evaluate(FrLocal::one()) != FrLocal::zero());
+    }
+}
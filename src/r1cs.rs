@@ -0,0 +1,330 @@
+use zksnark::{field::FieldIdentity, groth16::FrLocal};
+
+use crate::qap::{LinearCombination, Operation, QuadraticArithmeticProgram};
+
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum Visibility {
+    Public,
+    Private,
+}
+
+/// A handle to a variable of a `ConstraintSystem`
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) struct Variable {
+    index: usize,
+}
+
+/// Rank-1 constraint system: every constraint is `<A_i, w> * <B_i, w> = <C_i, w>` for the witness
+/// vector `w`, whose entry 0 is always the constant 1.
+///
+/// The rows of A, B and C are stored sparsely, as the non-zero `(variable index, coefficient)` pairs.
+pub(crate) struct ConstraintSystem {
+    visibilities: Vec<Visibility>,
+    a: Vec<LinearCombination>,
+    b: Vec<LinearCombination>,
+    c: Vec<LinearCombination>,
+}
+
+impl ConstraintSystem {
+    pub(crate) fn new() -> Self {
+        Self {
+            visibilities: vec![Visibility::Public],
+            a: vec![],
+            b: vec![],
+            c: vec![],
+        }
+    }
+
+    /// the variable that always holds 1, for constants in linear combinations
+    pub(crate) fn one(&self) -> Variable {
+        return Variable { index: 0 };
+    }
+
+    pub(crate) fn alloc_public(&mut self) -> Variable {
+        return self.alloc(Visibility::Public);
+    }
+
+    pub(crate) fn alloc_private(&mut self) -> Variable {
+        return self.alloc(Visibility::Private);
+    }
+
+    fn alloc(&mut self, visibility: Visibility) -> Variable {
+        self.visibilities.push(visibility);
+        return Variable {
+            index: self.visibilities.len() - 1,
+        };
+    }
+
+    /// adds the constraint `a * b = c`
+    pub(crate) fn enforce(
+        &mut self,
+        a: &[(Variable, FrLocal)],
+        b: &[(Variable, FrLocal)],
+        c: &[(Variable, FrLocal)],
+    ) -> &mut Self {
+        self.a.push(to_linear_combination(a));
+        self.b.push(to_linear_combination(b));
+        self.c.push(to_linear_combination(c));
+        return self;
+    }
+
+    pub(crate) fn number_of_variables(&self) -> usize {
+        return self.visibilities.len();
+    }
+
+    pub(crate) fn number_of_constraints(&self) -> usize {
+        return self.a.len();
+    }
+
+    /// the constant one variable comes first, it is public as the verifier knows its value
+    pub(crate) fn public_variables(&self) -> Vec<Variable> {
+        return (0..self.number_of_variables())
+            .filter(|index| self.visibilities[*index] == Visibility::Public)
+            .map(|index| Variable { index })
+            .collect();
+    }
+
+    /// the indices of the public variables other than the constant one, as
+    /// `CrsPlayer::create_computation_keys` takes them
+    pub(crate) fn public_inputs(&self) -> Vec<usize> {
+        return self
+            .public_variables()
+            .into_iter()
+            .filter(|variable| *variable != self.one())
+            .map(|variable| variable.index)
+            .collect();
+    }
+
+    /// Puts the assigned values into a witness vector, with the constant 1 at index 0.
+    pub(crate) fn witness(
+        &self,
+        assignments: &[(Variable, FrLocal)],
+    ) -> Result<Vec<FrLocal>, AssignmentError> {
+        let mut values: Vec<Option<FrLocal>> = vec![None; self.number_of_variables()];
+        values[0] = Some(FrLocal::one());
+        for (variable, value) in assignments {
+            let Some(slot) = values.get_mut(variable.index) else {
+                return Err(AssignmentError::UnknownVariable {
+                    variable: variable.index,
+                });
+            };
+            *slot = Some(*value);
+        }
+        return values
+            .into_iter()
+            .enumerate()
+            .map(|(variable, value)| value.ok_or(AssignmentError::UnassignedVariable { variable }))
+            .collect();
+    }
+
+    pub(crate) fn is_satisfied(&self, witness: &[FrLocal]) -> bool {
+        if witness.len() != self.number_of_variables() || witness[0] != FrLocal::one() {
+            return false;
+        }
+        return (0..self.number_of_constraints()).all(|row| {
+            evaluate(&self.a[row], witness) * evaluate(&self.b[row], witness)
+                == evaluate(&self.c[row], witness)
+        });
+    }
+
+    /// One QAP operation per constraint, so that `qap.prover_polynomial(witness)` is what
+    /// `Prover::new` takes.
+    pub(crate) fn to_qap(&self) -> QuadraticArithmeticProgram {
+        let operations: Vec<Operation> = (0..self.number_of_constraints())
+            .map(|row| Operation {
+                left: self.a[row].clone(),
+                right: self.b[row].clone(),
+                output: self.c[row].clone(),
+            })
+            .collect();
+        return QuadraticArithmeticProgram::new(self.number_of_variables(), &operations);
+    }
+}
+
+/// Why `ConstraintSystem::witness` could not build a witness from its assignments
+#[derive(Debug, PartialEq)]
+pub(crate) enum AssignmentError {
+    /// the variable was allocated but given no value
+    UnassignedVariable { variable: usize },
+    /// the variable was allocated by another, larger constraint system
+    UnknownVariable { variable: usize },
+}
+
+fn to_linear_combination(terms: &[(Variable, FrLocal)]) -> LinearCombination {
+    return terms
+        .iter()
+        .map(|(variable, coefficient)| (variable.index, *coefficient))
+        .collect();
+}
+
+fn evaluate(linear_combination: &LinearCombination, witness: &[FrLocal]) -> FrLocal {
+    return linear_combination
+        .iter()
+        .fold(FrLocal::zero(), |acc, (index, coefficient)| {
+            acc + *coefficient * witness[*index]
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        crs_player::{ComputationSecrets, CrsPlayer},
+        pairing_engine::ZksnarkEngine,
+        prover::ComputationProver,
+        test_fixtures::prove_and_verify,
+        verificiation::verify_computation,
+    };
+
+    struct CubicStatement {
+        constraint_system: ConstraintSystem,
+        x: Variable,
+        x_squared: Variable,
+        x_cubed: Variable,
+        out: Variable,
+    }
+
+    /// `x^3 + x + 5 = out`
+    fn cubic_statement() -> CubicStatement {
+        let one = FrLocal::one();
+        let mut constraint_system = ConstraintSystem::new();
+        let out = constraint_system.alloc_public();
+        let x = constraint_system.alloc_private();
+        let x_squared = constraint_system.alloc_private();
+        let x_cubed = constraint_system.alloc_private();
+        let constant = constraint_system.one();
+
+        constraint_system
+            .enforce(&[(x, one)], &[(x, one)], &[(x_squared, one)])
+            .enforce(&[(x_squared, one)], &[(x, one)], &[(x_cubed, one)])
+            .enforce(
+                &[(x_cubed, one), (x, one), (constant, FrLocal::from(5))],
+                &[(constant, one)],
+                &[(out, one)],
+            );
+
+        return CubicStatement {
+            constraint_system,
+            x,
+            x_squared,
+            x_cubed,
+            out,
+        };
+    }
+
+    fn witness(statement: &CubicStatement, x: usize, out: usize) -> Vec<FrLocal> {
+        return statement
+            .constraint_system
+            .witness(&[
+                (statement.x, FrLocal::from(x)),
+                (statement.x_squared, FrLocal::from(x * x)),
+                (statement.x_cubed, FrLocal::from(x * x * x)),
+                (statement.out, FrLocal::from(out)),
+            ])
+            .expect("every variable is assigned");
+    }
+
+    #[test]
+    fn variables_are_allocated_with_their_visibility() {
+        let statement = cubic_statement();
+        let constraint_system = &statement.constraint_system;
+
+        assert_eq!(constraint_system.number_of_variables(), 5);
+        assert_eq!(constraint_system.number_of_constraints(), 3);
+        assert!(
+            constraint_system.public_variables() == vec![constraint_system.one(), statement.out]
+        );
+        assert_eq!(constraint_system.public_inputs(), vec![1]);
+    }
+
+    #[test]
+    fn satisfiability_checks_every_constraint() {
+        let statement = cubic_statement();
+
+        assert!(statement
+            .constraint_system
+            .is_satisfied(&witness(&statement, 3, 35)));
+        assert!(!statement
+            .constraint_system
+            .is_satisfied(&witness(&statement, 3, 36)));
+
+        let mut wrong_constant = witness(&statement, 3, 35);
+        wrong_constant[0] = FrLocal::from(2);
+        assert!(!statement.constraint_system.is_satisfied(&wrong_constant));
+    }
+
+    #[test]
+    fn satisfying_witness_proves_the_constraint_system() {
+        let statement = cubic_statement();
+
+        let qap = statement.constraint_system.to_qap();
+
+        assert!(prove_and_verify(&qap, &witness(&statement, 3, 35)));
+        assert!(!prove_and_verify(&qap, &witness(&statement, 3, 36)));
+    }
+
+    #[test]
+    fn verifier_checks_the_public_output_it_expects() {
+        let statement = cubic_statement();
+        let qap = statement.constraint_system.to_qap();
+        let (proving_key, verifying_key) = CrsPlayer::<ZksnarkEngine>::with_engine(20, 21)
+            .create_computation_keys(
+                &qap,
+                &statement.constraint_system.public_inputs(),
+                &ComputationSecrets {
+                    alpha_l: 22,
+                    alpha_r: 23,
+                    alpha_o: 24,
+                    beta: 25,
+                    gamma: 26,
+                    rho_l: 27,
+                    rho_r: 28,
+                },
+            )
+            .expect("valid public inputs");
+        let proof = ComputationProver::new(&qap, witness(&statement, 3, 35))
+            .prove(&proving_key)
+            .expect("a satisfying witness");
+
+        assert!(verify_computation(
+            &verifying_key,
+            &[FrLocal::from(35)],
+            &proof
+        ));
+        assert!(!verify_computation(
+            &verifying_key,
+            &[FrLocal::from(36)],
+            &proof
+        ));
+    }
+
+    #[test]
+    fn witness_refuses_an_incomplete_or_foreign_assignment() {
+        let statement = cubic_statement();
+        let one = FrLocal::one();
+
+        assert_eq!(
+            statement
+                .constraint_system
+                .witness(&[
+                    (statement.x, one),
+                    (statement.x_squared, one),
+                    (statement.out, one)
+                ])
+                .err(),
+            Some(AssignmentError::UnassignedVariable {
+                variable: statement.x_cubed.index
+            })
+        );
+
+        let mut larger_system = ConstraintSystem::new();
+        let foreign = (0..5)
+            .map(|_| larger_system.alloc_private())
+            .last()
+            .expect("five variables");
+        assert_eq!(
+            statement.constraint_system.witness(&[(foreign, one)]).err(),
+            Some(AssignmentError::UnknownVariable { variable: 5 })
+        );
+    }
+}
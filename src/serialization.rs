@@ -0,0 +1,306 @@
+// The bn crate encodes its values through rustc_serialize, the encoder and decoder here turn that
+// into plain bytes. Only bn's own types can be encoded: the zksnark crate wraps them without giving
+// access to them.
+
+use rustc_serialize::{Decodable, Decoder, Encodable, Encoder};
+
+/// `None` unless `bytes` is exactly the encoding of a point of the curve
+pub(crate) fn g1_from_bytes(bytes: &[u8]) -> Option<bn::G1> {
+    return from_bytes(bytes);
+}
+
+/// `None` unless `bytes` is exactly the encoding of a point of the curve in the order-r subgroup,
+/// which bn checks for G2
+pub(crate) fn g2_from_bytes(bytes: &[u8]) -> Option<bn::G2> {
+    return from_bytes(bytes);
+}
+
+/// Any value the bn crate can encode, such as its own `G1`, `G2` and `Fr`.
+///
+/// A point is the tag byte 4 then its big-endian affine `x` and `y`, or only the tag 0 for the point
+/// at infinity, with each coordinate of a G2 point in Fq2 taking 64 bytes. A scalar is its canonical
+/// value below the group order, big-endian.
+pub(crate) fn to_bytes<T: Encodable>(value: &T) -> Vec<u8> {
+    let mut encoder = ByteEncoder { bytes: vec![] };
+    value
+        .encode(&mut encoder)
+        .expect("bn encodes its values as bytes only");
+    return encoder.bytes;
+}
+
+fn from_bytes<T: Decodable>(bytes: &[u8]) -> Option<T> {
+    let mut decoder = ByteDecoder { bytes, position: 0 };
+    let value = T::decode(&mut decoder).ok()?;
+    if decoder.position != bytes.len() {
+        return None;
+    }
+    return Some(value);
+}
+
+/// Collects the bytes bn emits. The bn types only ever emit `u8`s inside structs, so every other
+/// primitive is refused rather than given an encoding nobody relies on.
+struct ByteEncoder {
+    bytes: Vec<u8>,
+}
+
+macro_rules! unsupported_primitives {
+    ($($method:ident($type:ty)),*) => {
+        $(
+            fn $method(&mut self, _v: $type) -> Result<(), Self::Error> {
+                return Err(concat!(stringify!($method), " is not supported"));
+            }
+        )*
+    };
+}
+
+macro_rules! transparent_compounds {
+    ($($method:ident($($argument:ident: $type:ty),*)),*) => {
+        $(
+            fn $method<F>(&mut self, $($argument: $type,)* f: F) -> Result<(), Self::Error>
+            where
+                F: FnOnce(&mut Self) -> Result<(), Self::Error>,
+            {
+                $(let _ = $argument;)*
+                return f(self);
+            }
+        )*
+    };
+}
+
+impl Encoder for ByteEncoder {
+    type Error = &'static str;
+
+    fn emit_u8(&mut self, v: u8) -> Result<(), Self::Error> {
+        self.bytes.push(v);
+        return Ok(());
+    }
+
+    fn emit_nil(&mut self) -> Result<(), Self::Error> {
+        return Err("emit_nil is not supported");
+    }
+
+    fn emit_option_none(&mut self) -> Result<(), Self::Error> {
+        return Err("emit_option_none is not supported");
+    }
+
+    unsupported_primitives!(
+        emit_usize(usize),
+        emit_u64(u64),
+        emit_u32(u32),
+        emit_u16(u16),
+        emit_isize(isize),
+        emit_i64(i64),
+        emit_i32(i32),
+        emit_i16(i16),
+        emit_i8(i8),
+        emit_bool(bool),
+        emit_f64(f64),
+        emit_f32(f32),
+        emit_char(char),
+        emit_str(&str)
+    );
+
+    transparent_compounds!(
+        emit_enum(name: &str),
+        emit_enum_variant(v_name: &str, v_id: usize, len: usize),
+        emit_enum_variant_arg(a_idx: usize),
+        emit_enum_struct_variant(v_name: &str, v_id: usize, len: usize),
+        emit_enum_struct_variant_field(f_name: &str, f_idx: usize),
+        emit_struct(name: &str, len: usize),
+        emit_struct_field(f_name: &str, f_idx: usize),
+        emit_tuple(len: usize),
+        emit_tuple_arg(idx: usize),
+        emit_tuple_struct(name: &str, len: usize),
+        emit_tuple_struct_arg(f_idx: usize),
+        emit_option(),
+        emit_option_some(),
+        emit_seq(len: usize),
+        emit_seq_elt(idx: usize),
+        emit_map(len: usize),
+        emit_map_elt_key(idx: usize),
+        emit_map_elt_val(idx: usize)
+    );
+}
+
+/// Hands bn the bytes `ByteEncoder` collected, one `u8` at a time
+struct ByteDecoder<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+macro_rules! unreadable_primitives {
+    ($($method:ident() -> $type:ty),*) => {
+        $(
+            fn $method(&mut self) -> Result<$type, Self::Error> {
+                return Err(concat!(stringify!($method), " is not supported").to_string());
+            }
+        )*
+    };
+}
+
+macro_rules! transparent_reads {
+    ($($method:ident($($argument:ident: $type:ty),*)),*) => {
+        $(
+            fn $method<T, F>(&mut self, $($argument: $type,)* f: F) -> Result<T, Self::Error>
+            where
+                F: FnOnce(&mut Self) -> Result<T, Self::Error>,
+            {
+                $(let _ = $argument;)*
+                return f(self);
+            }
+        )*
+    };
+}
+
+impl<'a> Decoder for ByteDecoder<'a> {
+    type Error = String;
+
+    fn read_u8(&mut self) -> Result<u8, Self::Error> {
+        let byte = *self
+            .bytes
+            .get(self.position)
+            .ok_or_else(|| "not enough bytes".to_string())?;
+        self.position += 1;
+        return Ok(byte);
+    }
+
+    fn error(&mut self, err: &str) -> Self::Error {
+        return err.to_string();
+    }
+
+    unreadable_primitives!(
+        read_nil() -> (),
+        read_usize() -> usize,
+        read_u64() -> u64,
+        read_u32() -> u32,
+        read_u16() -> u16,
+        read_isize() -> isize,
+        read_i64() -> i64,
+        read_i32() -> i32,
+        read_i16() -> i16,
+        read_i8() -> i8,
+        read_bool() -> bool,
+        read_f64() -> f64,
+        read_f32() -> f32,
+        read_char() -> char,
+        read_str() -> String
+    );
+
+    transparent_reads!(
+        read_enum(name: &str),
+        read_enum_variant_arg(a_idx: usize),
+        read_enum_struct_variant_field(f_name: &str, f_idx: usize),
+        read_struct(s_name: &str, len: usize),
+        read_struct_field(f_name: &str, f_idx: usize),
+        read_tuple(len: usize),
+        read_tuple_arg(a_idx: usize),
+        read_tuple_struct(s_name: &str, len: usize),
+        read_tuple_struct_arg(a_idx: usize),
+        read_seq_elt(idx: usize),
+        read_map_elt_key(idx: usize),
+        read_map_elt_val(idx: usize)
+    );
+
+    fn read_enum_variant<T, F>(&mut self, _names: &[&str], _f: F) -> Result<T, Self::Error>
+    where
+        F: FnMut(&mut Self, usize) -> Result<T, Self::Error>,
+    {
+        return Err("read_enum_variant is not supported".to_string());
+    }
+
+    fn read_enum_struct_variant<T, F>(&mut self, _names: &[&str], _f: F) -> Result<T, Self::Error>
+    where
+        F: FnMut(&mut Self, usize) -> Result<T, Self::Error>,
+    {
+        return Err("read_enum_struct_variant is not supported".to_string());
+    }
+
+    fn read_option<T, F>(&mut self, _f: F) -> Result<T, Self::Error>
+    where
+        F: FnMut(&mut Self, bool) -> Result<T, Self::Error>,
+    {
+        return Err("read_option is not supported".to_string());
+    }
+
+    fn read_seq<T, F>(&mut self, _f: F) -> Result<T, Self::Error>
+    where
+        F: FnOnce(&mut Self, usize) -> Result<T, Self::Error>,
+    {
+        return Err("read_seq is not supported".to_string());
+    }
+
+    fn read_map<T, F>(&mut self, _f: F) -> Result<T, Self::Error>
+    where
+        F: FnOnce(&mut Self, usize) -> Result<T, Self::Error>,
+    {
+        return Err("read_map is not supported".to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bn::Group;
+    use rustc_serialize::hex::ToHex;
+
+    use super::*;
+    use crate::pairing_engine::{BnEngine, PairingEngine};
+
+    #[test]
+    fn encodings_have_fixed_sizes() {
+        assert_eq!(to_bytes(&BnEngine::encrypt1(5)).len(), 1 + 2 * 32);
+        assert_eq!(to_bytes(&BnEngine::encrypt2(5)).len(), 1 + 2 * 64);
+        assert_eq!(to_bytes(&BnEngine::scalar(5)).len(), 32);
+        assert_eq!(to_bytes(&BnEngine::encrypt1(0)), vec![0]);
+    }
+
+    #[test]
+    fn encoding_does_not_depend_on_how_the_point_was_computed() {
+        assert!(
+            to_bytes(&(BnEngine::encrypt1(2) + BnEngine::encrypt1(3)))
+                == to_bytes(&BnEngine::encrypt1(5))
+        );
+        assert!(
+            to_bytes(&(BnEngine::encrypt2(2) + BnEngine::encrypt2(3)))
+                == to_bytes(&BnEngine::encrypt2(5))
+        );
+    }
+
+    #[test]
+    fn encodings_match_known_values() {
+        // bn's generator of G1 is (1, 2)
+        assert_eq!(
+            to_bytes(&bn::G1::one()).to_hex(),
+            format!("04{:064x}{:064x}", 1, 2)
+        );
+        assert_eq!(
+            to_bytes(&BnEngine::scalar(258)).to_hex(),
+            format!("{:064x}", 258)
+        );
+        // the group order minus one
+        assert!(
+            to_bytes(&-bn::Fr::one()).to_hex()
+                == "30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000000"
+        );
+    }
+
+    #[test]
+    fn decoding_inverts_encoding() {
+        for e in [BnEngine::encrypt1(0), BnEngine::encrypt1(5)] {
+            assert!(g1_from_bytes(&to_bytes(&e)) == Some(e));
+        }
+        for e in [BnEngine::encrypt2(0), BnEngine::encrypt2(5)] {
+            assert!(g2_from_bytes(&to_bytes(&e)) == Some(e));
+        }
+    }
+
+    #[test]
+    fn decoding_rejects_points_off_the_curve_and_wrong_lengths() {
+        let mut bytes = to_bytes(&BnEngine::encrypt1(5));
+        bytes[64] ^= 1;
+        assert!(g1_from_bytes(&bytes).is_none());
+
+        let bytes = to_bytes(&BnEngine::encrypt1(5));
+        assert!(g1_from_bytes(&bytes[..64]).is_none());
+        assert!(g1_from_bytes(&[bytes.clone(), vec![0]].concat()).is_none());
+    }
+}
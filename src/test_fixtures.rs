@@ -0,0 +1,31 @@
+// Fixtures shared by the tests of several modules.
+
+use zksnark::groth16::{coefficient_poly::CoefficientPoly, FrLocal};
+
+use crate::{
+    crs_player::CrsPlayer, pairing_engine::ZksnarkEngine, prover::Prover,
+    qap::QuadraticArithmeticProgram, verificiation::verify, ProofError, ProvingKey, PublicData,
+    VerifyingKey,
+};
+
+/// Proves the QAP's polynomial for `witness` with the protocol of section 3 and verifies the proof.
+/// An unsatisfying witness leaves a remainder, so there is no proof to send and the result is false.
+pub(crate) fn prove_and_verify(qap: &QuadraticArithmeticProgram, witness: &[FrLocal]) -> bool {
+    let public = PublicData::<CoefficientPoly<FrLocal>, ZksnarkEngine> {
+        target_polynomial: qap.target_polynomial().clone(),
+        crs: CrsPlayer::with_engine(12, 13).start_crs_ceremony().accumulator,
+    };
+
+    let prover = Prover::new(qap.prover_polynomial(witness));
+    let proof = match prover.prove(&ProvingKey::new(&public)) {
+        Ok(proof) => proof,
+        Err(error) => {
+            assert_eq!(error, ProofError::NotAMultipleOfTarget);
+            return false;
+        }
+    };
+    return verify(
+        &VerifyingKey::new(&public).expect("target degree within the CRS"),
+        &proof,
+    );
+}
@@ -0,0 +1,279 @@
+// A deliberately insecure backend for walkthroughs: the groups are small enough that every encrypted
+// value can be decrypted, so a demo can print the `s`, `alpha` and `p(s)` hidden in the CRS and proofs.
+//
+// The group is the subgroup of order r of the integers modulo the safe prime p = 2r + 1. It serves
+// as G1, G2 and Gt at once, and the "pairing" takes both discrete logarithms and multiplies them.
+
+use std::{
+    collections::HashMap,
+    ops::{Add, Mul, Sub},
+    sync::OnceLock,
+};
+
+use crate::pairing_engine::{EncodableEngine, PairingEngine};
+
+/// the order of the group, and the modulus of the exponents
+const GROUP_ORDER: u64 = 1048889;
+
+/// `2 * GROUP_ORDER + 1`
+const MODULUS: u64 = 2097779;
+
+/// a square other than 1, so it generates the subgroup of order r
+const GENERATOR: u64 = 4;
+
+/// An exponent modulo the group order
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) struct ToyScalar(u64);
+
+impl Add for ToyScalar {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        return ToyScalar((self.0 + other.0) % GROUP_ORDER);
+    }
+}
+
+impl Mul for ToyScalar {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        return ToyScalar(self.0 * other.0 % GROUP_ORDER);
+    }
+}
+
+/// `GENERATOR^x mod MODULUS`, written additively like the other backends' group elements
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) struct ToyGroupElement(u64);
+
+impl Add for ToyGroupElement {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        return ToyGroupElement(self.0 * other.0 % MODULUS);
+    }
+}
+
+impl Sub for ToyGroupElement {
+    type Output = Self;
+
+    /// multiplies by the inverse, `a^(r-1)` as every element has order dividing r
+    fn sub(self, other: Self) -> Self {
+        return self + ToyGroupElement(power_mod(other.0, GROUP_ORDER - 1));
+    }
+}
+
+pub(crate) struct ToyEngine;
+
+impl PairingEngine for ToyEngine {
+    type Scalar = ToyScalar;
+    type G1 = ToyGroupElement;
+    type G2 = ToyGroupElement;
+    type Gt = ToyGroupElement;
+
+    fn scalar(n: usize) -> ToyScalar {
+        return ToyScalar(n as u64 % GROUP_ORDER);
+    }
+
+    fn generator1() -> ToyGroupElement {
+        return ToyGroupElement(GENERATOR);
+    }
+
+    fn generator2() -> ToyGroupElement {
+        return ToyGroupElement(GENERATOR);
+    }
+
+    fn multiply1(e_a: ToyGroupElement, b: ToyScalar) -> ToyGroupElement {
+        return ToyGroupElement(power_mod(e_a.0, b.0));
+    }
+
+    fn multiply2(e_a: ToyGroupElement, b: ToyScalar) -> ToyGroupElement {
+        return ToyGroupElement(power_mod(e_a.0, b.0));
+    }
+
+    /// `e(g^a, g^b) = g^(ab)`, found by taking both discrete logarithms
+    fn pairing(e_a: ToyGroupElement, e_b: ToyGroupElement) -> ToyGroupElement {
+        return ToyEngine::multiply1(e_b, ToyEngine::scalar(decrypt(e_a)));
+    }
+
+    fn gt_multiply(a: ToyGroupElement, b: ToyGroupElement) -> ToyGroupElement {
+        return a + b;
+    }
+
+    fn gt_one() -> ToyGroupElement {
+        return ToyGroupElement(1);
+    }
+
+    fn describe1(e: &ToyGroupElement) -> String {
+        return format!("g^{}", decrypt(*e));
+    }
+
+    fn describe2(e: &ToyGroupElement) -> String {
+        return format!("g^{}", decrypt(*e));
+    }
+}
+
+impl EncodableEngine for ToyEngine {
+    fn scalar_to_bytes(x: &ToyScalar) -> Vec<u8> {
+        return x.0.to_be_bytes().to_vec();
+    }
+
+    fn g1_to_bytes(e: &ToyGroupElement) -> Vec<u8> {
+        return e.0.to_be_bytes().to_vec();
+    }
+
+    fn g2_to_bytes(e: &ToyGroupElement) -> Vec<u8> {
+        return e.0.to_be_bytes().to_vec();
+    }
+
+    fn scalar_from_wide_bytes(bytes: &[u8; 64]) -> ToyScalar {
+        return ToyScalar(
+            bytes
+                .iter()
+                .fold(0, |acc, byte| (acc * 256 + *byte as u64) % GROUP_ORDER),
+        );
+    }
+}
+
+/// The discrete logarithm of `e`, the value it encrypts modulo the group order.
+///
+/// Baby-step giant-step: `e = g^(i m + j)` for the `j` in a table of `g^j`, found after at most `m`
+/// giant steps of `g^-m`, with `m` about the square root of the group order.
+pub(crate) fn decrypt(e: ToyGroupElement) -> usize {
+    let (step_count, baby_steps) = baby_step_table();
+    let giant_step = power_mod(GENERATOR, GROUP_ORDER - step_count);
+
+    let mut candidate = e.0;
+    for i in 0..*step_count {
+        if let Some(j) = baby_steps.get(&candidate) {
+            return ((i * step_count + j) % GROUP_ORDER) as usize;
+        }
+        candidate = candidate * giant_step % MODULUS;
+    }
+    panic!("{} is not in the group", e.0);
+}
+
+/// `m` and the map from `g^j` to `j` for `j < m`
+fn baby_step_table() -> &'static (u64, HashMap<u64, u64>) {
+    static TABLE: OnceLock<(u64, HashMap<u64, u64>)> = OnceLock::new();
+    return TABLE.get_or_init(|| {
+        let step_count = (GROUP_ORDER as f64).sqrt().ceil() as u64;
+        let mut baby_steps = HashMap::new();
+        let mut power = 1;
+        for j in 0..step_count {
+            baby_steps.insert(power, j);
+            power = power * GENERATOR % MODULUS;
+        }
+        return (step_count, baby_steps);
+    });
+}
+
+fn power_mod(base: u64, exponent: u64) -> u64 {
+    let mut result = 1;
+    let mut square = base % MODULUS;
+    let mut remaining = exponent;
+    while remaining > 0 {
+        if remaining & 1 == 1 {
+            result = result * square % MODULUS;
+        }
+        square = square * square % MODULUS;
+        remaining >>= 1;
+    }
+    return result;
+}
+
+#[cfg(test)]
+mod tests {
+    use polynomen::Poly;
+
+    use super::*;
+    use crate::{
+        crs_player::CrsPlayer, prover::Prover, verificiation::verify, ProvingKey, PublicData,
+        VerifyingKey,
+    };
+
+    fn public_data() -> PublicData<Poly<usize>, ToyEngine> {
+        return PublicData {
+            target_polynomial: Poly::new_from_coeffs(&[3, 4, 5]),
+            crs: CrsPlayer::<ToyEngine>::with_engine(12, 13)
+                .start_crs_ceremony()
+                .accumulator,
+        };
+    }
+
+    #[test]
+    fn decryption_inverts_encryption() {
+        for n in [0, 1, 2, 1000, 1048888] {
+            assert_eq!(decrypt(ToyEngine::encrypt1(n)), n);
+        }
+        assert_eq!(decrypt(ToyEngine::encrypt1(1048889 + 5)), 5);
+        assert_eq!(
+            decrypt(ToyEngine::mult_and_encrypt1(1024, 2048)),
+            1024 * 2048 % 1048889
+        );
+    }
+
+    #[test]
+    fn pairing_multiplies_the_exponents() {
+        assert!(
+            ToyEngine::pairing(ToyEngine::encrypt1(6), ToyEngine::encrypt2(7))
+                == ToyEngine::encrypt1(42)
+        );
+        assert!(
+            ToyEngine::negate1(ToyEngine::encrypt1(5)) + ToyEngine::encrypt1(5)
+                == ToyEngine::encrypt1(0)
+        );
+    }
+
+    #[test]
+    fn crs_decrypts_to_the_powers_of_s() {
+        let crs = public_data().crs;
+
+        assert_eq!(decrypt(crs.encrypted2_alpha), 13);
+        for (k, (encrypted1_s_power, encrypted1_alpha_times_s_power)) in crs
+            .encrypted1_s_powers
+            .iter()
+            .zip(&crs.encrypted1_alpha_times_s_powers)
+            .enumerate()
+        {
+            assert_eq!(
+                decrypt(*encrypted1_s_power),
+                12usize.pow(k as u32) % 1048889
+            );
+            assert_eq!(
+                decrypt(*encrypted1_alpha_times_s_power),
+                13 * 12usize.pow(k as u32) % 1048889
+            );
+        }
+    }
+
+    #[test]
+    fn proofs_decrypt_to_the_polynomial_values() {
+        let public = public_data();
+        let key = ProvingKey::new(&public);
+        let prover = Prover::new(&public.target_polynomial * &Poly::new_from_coeffs(&[6, 7]));
+
+        // 7 is the prover's random entropy
+        let proof = prover.prove(&key).expect("degree within the CRS");
+        let p_at_s = (3 + 4 * 12 + 5 * 144) * (6 + 7 * 12);
+        assert_eq!(
+            decrypt(proof.encrypted1_secret_poly_at_s),
+            7 * p_at_s % 1048889
+        );
+        assert_eq!(decrypt(proof.encrypted1_ratio_poly_at_s), 7 * (6 + 7 * 12));
+        assert!(verify(
+            &VerifyingKey::new(&public).expect("target degree within the CRS"),
+            &proof
+        ));
+
+        // the error adds alpha s^2 to the alpha-shifted value only
+        let erroneous_proof = prover.erroneous_prove(&key).expect("degree within the CRS");
+        assert_eq!(
+            decrypt(erroneous_proof.encrypted1_alpha_times_secret_poly_at_s),
+            7 * (13 * p_at_s + 13 * 144) % 1048889
+        );
+        assert!(!verify(
+            &VerifyingKey::new(&public).expect("target degree within the CRS"),
+            &erroneous_proof
+        ));
+    }
+}
@@ -0,0 +1,214 @@
+// An opt-in record of what the protocol does, for reading alongside the paper.
+//
+// The ceremony, `Prover::prove` and `verify` note each stage as they run. Notes are dropped unless a
+// caller is inside `record`, and their values are only formatted then, so untraced runs pay nothing.
+
+use std::cell::RefCell;
+
+/// One stage of the protocol, with the section of the paper that explains it
+pub(crate) struct TraceStep {
+    section: &'static str,
+    stage: &'static str,
+    description: String,
+    values: Vec<(String, String)>,
+}
+
+/// The stages recorded by `record`, in the order they ran
+pub(crate) struct Trace {
+    steps: Vec<TraceStep>,
+}
+
+thread_local! {
+    static RECORDING: RefCell<Option<Vec<TraceStep>>> = const { RefCell::new(None) };
+}
+
+/// Runs `f` and returns what it noted along with its result
+pub(crate) fn record<T>(f: impl FnOnce() -> T) -> (T, Trace) {
+    RECORDING.with(|recording| {
+        let mut recording = recording.borrow_mut();
+        assert!(recording.is_none(), "traces do not nest");
+        *recording = Some(vec![]);
+    });
+
+    let result = f();
+
+    let steps = RECORDING.with(|recording| recording.borrow_mut().take().expect("still recording"));
+    return (result, Trace { steps });
+}
+
+/// Adds a step to the trace being recorded, if any. `values` names and formats what the stage
+/// computed, and is only called when recording.
+pub(crate) fn note(
+    section: &'static str,
+    stage: &'static str,
+    description: &str,
+    values: impl FnOnce() -> Vec<(&'static str, String)>,
+) {
+    RECORDING.with(|recording| {
+        if let Some(steps) = recording.borrow_mut().as_mut() {
+            steps.push(TraceStep {
+                section,
+                stage,
+                description: description.to_string(),
+                values: values()
+                    .into_iter()
+                    .map(|(name, value)| (name.to_string(), value))
+                    .collect(),
+            });
+        }
+    });
+}
+
+impl Trace {
+    pub(crate) fn render_text(&self) -> String {
+        let mut text = String::new();
+        for (index, step) in self.steps.iter().enumerate() {
+            text += &format!(
+                "{}. {} (section {})\n   {}\n",
+                index + 1,
+                step.stage,
+                step.section,
+                step.description
+            );
+            for (name, value) in &step.values {
+                text += &format!("     {}: {}\n", name, value);
+            }
+        }
+        return text;
+    }
+
+    pub(crate) fn render_markdown(&self) -> String {
+        let mut markdown = String::new();
+        for (index, step) in self.steps.iter().enumerate() {
+            markdown += &format!(
+                "### {}. {} (section {})\n\n{}\n\n",
+                index + 1,
+                step.stage,
+                step.section,
+                step.description
+            );
+            if !step.values.is_empty() {
+                markdown += "| value | |\n|---|---|\n";
+                for (name, value) in &step.values {
+                    markdown += &format!("| {} | `{}` |\n", name, value);
+                }
+                markdown += "\n";
+            }
+        }
+        return markdown;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use polynomen::Poly;
+
+    use super::*;
+    use crate::{
+        cheating_prover::{CheatingProver, CheatingStrategy},
+        crs_player::CrsPlayer,
+        prover::Prover,
+        toy_engine::ToyEngine,
+        verificiation::verify,
+        CrsCeremonyTranscript, Proof, ProvingKey, PublicData, VerifyingKey,
+    };
+
+    /// a two-player ceremony, a proof and its verification on the toy backend
+    fn run_protocol(
+        prove: impl Fn(&ProvingKey<Poly<usize>, ToyEngine>) -> Proof<ToyEngine>,
+    ) -> bool {
+        let mut transcript: CrsCeremonyTranscript<ToyEngine> = CrsCeremonyTranscript {
+            history: vec![CrsPlayer::with_engine(12, 13).start_crs_ceremony()],
+        };
+        transcript.history.push(
+            CrsPlayer::with_engine(14, 15)
+                .continue_crs_ceremony(&transcript)
+                .expect("honest transcript"),
+        );
+        let public = PublicData {
+            target_polynomial: Poly::new_from_coeffs(&[3, 4, 5]),
+            crs: transcript.history.pop().expect("non-empty").accumulator,
+        };
+
+        let proof = prove(&ProvingKey::new(&public));
+        return verify(
+            &VerifyingKey::new(&public).expect("target degree within the CRS"),
+            &proof,
+        );
+    }
+
+    fn honest_proof(key: &ProvingKey<Poly<usize>, ToyEngine>) -> Proof<ToyEngine> {
+        return Prover::new(Poly::new_from_coeffs(&[3, 4, 5]) * Poly::new_from_coeffs(&[6, 7]))
+            .prove(key)
+            .expect("degree within the CRS");
+    }
+
+    #[test]
+    fn every_stage_is_recorded_in_order() {
+        let (valid, trace) = record(|| run_protocol(honest_proof));
+        assert!(valid);
+
+        let sections: Vec<&str> = trace.steps.iter().map(|step| step.section).collect();
+        assert_eq!(
+            sections,
+            vec!["3.6.3", "3.6.3", "3.6.3", "3.2", "3.3", "3.4", "3.5", "3.6.1", "3.6.1"]
+        );
+    }
+
+    #[test]
+    fn toy_values_show_the_hidden_exponents() {
+        let (_, trace) = record(|| run_protocol(honest_proof));
+
+        let quotient = trace
+            .steps
+            .iter()
+            .find(|step| step.section == "3.2")
+            .expect("the quotient is recorded");
+        assert_eq!(
+            quotient.values[1],
+            ("h(x)".to_string(), "6 +7s".to_string())
+        );
+
+        // s = 12 * 14 and alpha = 13 * 15 after both contributions
+        let evaluation = trace
+            .steps
+            .iter()
+            .find(|step| step.section == "3.3")
+            .expect("the evaluation is recorded");
+        let t_at_s = 3 + 4 * 168 + 5 * 168 * 168;
+        assert_eq!(
+            evaluation.values[0].1,
+            format!("g^{}", (6 + 7 * 168) * t_at_s % 1048889)
+        );
+    }
+
+    #[test]
+    fn failing_equation_is_marked() {
+        let cheating_prover = CheatingProver::new(CheatingStrategy::NotDivisibleByTarget);
+        let (valid, trace) = record(|| {
+            run_protocol(|key| cheating_prover.prove(key).expect("degree within the CRS"))
+        });
+        assert!(!valid);
+
+        let text = trace.render_text();
+        assert!(text.contains("holds: false"));
+        assert!(text.contains("holds: true"));
+    }
+
+    #[test]
+    fn nothing_is_recorded_outside_of_record() {
+        run_protocol(honest_proof);
+        let (_, trace) = record(|| ());
+        assert!(trace.steps.is_empty());
+    }
+
+    #[test]
+    fn markdown_has_a_heading_per_step() {
+        let (_, trace) = record(|| run_protocol(honest_proof));
+        let markdown = trace.render_markdown();
+
+        assert_eq!(markdown.matches("\n### ").count() + 1, trace.steps.len());
+        assert!(markdown.starts_with("### 1. "));
+        assert!(markdown.contains("| h(x) | `6 +7s` |"));
+    }
+}
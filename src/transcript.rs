@@ -0,0 +1,264 @@
+use std::marker::PhantomData;
+
+use polynomen::Poly;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    pairing_engine::{BnEngine, EncodableEngine},
+    CrsCeremonyStep, Proof, PublicData, CRS,
+};
+
+/// A Fiat-Shamir transcript: every message of a protocol is absorbed with a label, and the
+/// challenges a verifier would have picked are squeezed out of the hash of everything so far.
+///
+/// Labels and values are length-prefixed, so no two different sequences of messages are absorbed as
+/// the same bytes. Each squeezed challenge is absorbed too, so the next one differs from it.
+///
+/// Group and field elements are absorbed as the backend `E` encodes them. It defaults to the engine
+/// of the protocol's types, so their default values can be absorbed as they are.
+#[derive(Clone)]
+pub(crate) struct Transcript<E: EncodableEngine = BnEngine> {
+    hasher: Sha256,
+    engine: PhantomData<E>,
+}
+
+impl<E: EncodableEngine> Transcript<E> {
+    /// `domain_separation_label` names the protocol, so challenges of one protocol never answer for
+    /// another
+    pub(crate) fn new(domain_separation_label: &str) -> Self {
+        let mut transcript = Self {
+            hasher: Sha256::new(),
+            engine: PhantomData,
+        };
+        transcript.append_bytes(
+            "learning-zksnarks transcript v1",
+            domain_separation_label.as_bytes(),
+        );
+        return transcript;
+    }
+
+    pub(crate) fn append_bytes(&mut self, label: &str, bytes: &[u8]) {
+        self.hasher.update((label.len() as u64).to_be_bytes());
+        self.hasher.update(label.as_bytes());
+        self.hasher.update((bytes.len() as u64).to_be_bytes());
+        self.hasher.update(bytes);
+    }
+
+    pub(crate) fn append_g1(&mut self, label: &str, e: &E::G1) {
+        self.append_bytes(label, &E::g1_to_bytes(e));
+    }
+
+    pub(crate) fn append_g2(&mut self, label: &str, e: &E::G2) {
+        self.append_bytes(label, &E::g2_to_bytes(e));
+    }
+
+    pub(crate) fn append_g1_vector(&mut self, label: &str, elements: &[E::G1]) {
+        let bytes: Vec<u8> = elements.iter().flat_map(E::g1_to_bytes).collect();
+        self.append_bytes(label, &bytes);
+    }
+
+    pub(crate) fn append_g2_vector(&mut self, label: &str, elements: &[E::G2]) {
+        let bytes: Vec<u8> = elements.iter().flat_map(E::g2_to_bytes).collect();
+        self.append_bytes(label, &bytes);
+    }
+
+    pub(crate) fn append_field_element(&mut self, label: &str, x: &E::Scalar) {
+        self.append_bytes(label, &E::scalar_to_bytes(x));
+    }
+
+    /// the coefficients from the constant one up, each as a big-endian u64
+    pub(crate) fn append_polynomial(&mut self, label: &str, polynomial: &Poly<usize>) {
+        let bytes: Vec<u8> = polynomial
+            .coeffs()
+            .iter()
+            .flat_map(|coefficient| (*coefficient as u64).to_be_bytes())
+            .collect();
+        self.append_bytes(label, &bytes);
+    }
+
+    /// 64 bytes of hash output reduced modulo the group order, so the challenge is close to uniform
+    pub(crate) fn challenge_field_element(&mut self, label: &str) -> E::Scalar {
+        self.append_bytes(label, &[]);
+
+        let mut wide_bytes = [0; 64];
+        for (counter, half) in wide_bytes.chunks_mut(32).enumerate() {
+            let mut hasher = self.hasher.clone();
+            hasher.update([counter as u8]);
+            half.copy_from_slice(&hasher.finalize());
+        }
+
+        let challenge = E::scalar_from_wide_bytes(&wide_bytes);
+        self.append_field_element(label, &challenge);
+        return challenge;
+    }
+}
+
+/// A value a transcript can absorb, with each of its parts under its own label
+pub(crate) trait AppendToTranscript<E: EncodableEngine = BnEngine> {
+    fn append_to_transcript(&self, label: &str, transcript: &mut Transcript<E>);
+}
+
+impl<E: EncodableEngine> AppendToTranscript<E> for CRS<E> {
+    fn append_to_transcript(&self, label: &str, transcript: &mut Transcript<E>) {
+        transcript.append_g2(&format!("{label}.encrypted2_alpha"), &self.encrypted2_alpha);
+        transcript.append_g2_vector(
+            &format!("{label}.encrypted2_s_powers"),
+            &self.encrypted2_s_powers,
+        );
+        transcript.append_g1_vector(
+            &format!("{label}.encrypted1_s_powers"),
+            &self.encrypted1_s_powers,
+        );
+        transcript.append_g1_vector(
+            &format!("{label}.encrypted1_alpha_times_s_powers"),
+            &self.encrypted1_alpha_times_s_powers,
+        );
+    }
+}
+
+impl<E: EncodableEngine> AppendToTranscript<E> for PublicData<Poly<usize>, E> {
+    fn append_to_transcript(&self, label: &str, transcript: &mut Transcript<E>) {
+        transcript.append_polynomial(
+            &format!("{label}.target_polynomial"),
+            &self.target_polynomial,
+        );
+        self.crs
+            .append_to_transcript(&format!("{label}.crs"), transcript);
+    }
+}
+
+impl<E: EncodableEngine> AppendToTranscript<E> for Proof<E> {
+    fn append_to_transcript(&self, label: &str, transcript: &mut Transcript<E>) {
+        transcript.append_g1(
+            &format!("{label}.encrypted1_ratio_poly_at_s"),
+            &self.encrypted1_ratio_poly_at_s,
+        );
+        transcript.append_g1(
+            &format!("{label}.encrypted1_secret_poly_at_s"),
+            &self.encrypted1_secret_poly_at_s,
+        );
+        transcript.append_g1(
+            &format!("{label}.encrypted1_alpha_times_secret_poly_at_s"),
+            &self.encrypted1_alpha_times_secret_poly_at_s,
+        );
+    }
+}
+
+impl<E: EncodableEngine> AppendToTranscript<E> for CrsCeremonyStep<E> {
+    fn append_to_transcript(&self, label: &str, transcript: &mut Transcript<E>) {
+        transcript.append_g1_vector(
+            &format!("{label}.encrypted1_s_powers"),
+            &self.encrypted1_s_powers,
+        );
+        transcript.append_g1(&format!("{label}.encrypted1_alpha"), &self.encrypted1_alpha);
+        transcript.append_g2_vector(
+            &format!("{label}.encrypted2_alpha_times_s_powers"),
+            &self.encrypted2_alpha_times_s_powers,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rustc_serialize::hex::ToHex;
+
+    use super::*;
+    use crate::{crs_player::CrsPlayer, pairing_engine::PairingEngine, prover::Prover, ProvingKey};
+
+    fn challenge_hex(transcript: &mut Transcript<BnEngine>, label: &str) -> String {
+        return BnEngine::scalar_to_bytes(&transcript.challenge_field_element(label)).to_hex();
+    }
+
+    #[test]
+    fn empty_transcript_challenges_are_pinned() {
+        let mut transcript = Transcript::new("test");
+
+        assert_eq!(
+            challenge_hex(&mut transcript, "first"),
+            "21bc9e2e8899d6c3928658238fb250f2ac5aee4e38f9bc8e8f1f084123dd9a26"
+        );
+        assert_eq!(
+            challenge_hex(&mut transcript, "second"),
+            "01e5e809483dc8cead150b2c0afea7eaaa61f694d8cee1c2b81df4f13493fca0"
+        );
+    }
+
+    #[test]
+    fn protocol_values_challenge_is_pinned() {
+        let player = CrsPlayer::<BnEngine>::with_engine(12, 13);
+        let ceremony_values = player.start_crs_ceremony();
+        let public = PublicData {
+            target_polynomial: Poly::new_from_coeffs(&[3, 4, 5]),
+            crs: ceremony_values.accumulator,
+        };
+        let proof = Prover::new(&public.target_polynomial * &Poly::new_from_coeffs(&[6, 7]))
+            .prove(&ProvingKey::new(&public))
+            .expect("degree within the CRS");
+
+        let mut transcript = Transcript::new("test");
+        public.append_to_transcript("public", &mut transcript);
+        ceremony_values
+            .step
+            .append_to_transcript("step", &mut transcript);
+        proof.append_to_transcript("proof", &mut transcript);
+
+        assert_eq!(
+            challenge_hex(&mut transcript, "challenge"),
+            "0cd3531b1b508ad9773fa6228c86fc65e4cf1133e92e08daca9424160653a7ba"
+        );
+    }
+
+    /// the protocol's types without an engine named anywhere, pinned to the same challenge as
+    /// `protocol_values_challenge_is_pinned` since their default engine is bn
+    #[test]
+    fn default_engine_values_challenge_is_pinned() {
+        let ceremony_values = CrsPlayer::new(12, 13).start_crs_ceremony();
+        let public: PublicData = PublicData {
+            target_polynomial: Poly::new_from_coeffs(&[3, 4, 5]),
+            crs: ceremony_values.accumulator,
+        };
+        let proof: Proof = Prover::new(&public.target_polynomial * &Poly::new_from_coeffs(&[6, 7]))
+            .prove(&ProvingKey::new(&public))
+            .expect("degree within the CRS");
+
+        let mut transcript: Transcript = Transcript::new("test");
+        public.append_to_transcript("public", &mut transcript);
+        ceremony_values
+            .step
+            .append_to_transcript("step", &mut transcript);
+        proof.append_to_transcript("proof", &mut transcript);
+
+        assert_eq!(
+            challenge_hex(&mut transcript, "challenge"),
+            "0cd3531b1b508ad9773fa6228c86fc65e4cf1133e92e08daca9424160653a7ba"
+        );
+    }
+
+    #[test]
+    fn challenges_depend_on_labels_values_and_domain() {
+        let challenge = |domain: &str, label: &str, value: usize| -> bn::Fr {
+            let mut transcript = Transcript::<BnEngine>::new(domain);
+            transcript.append_field_element(label, &BnEngine::scalar(value));
+            return transcript.challenge_field_element("challenge");
+        };
+
+        let reference = challenge("test", "x", 1);
+        assert!(reference == challenge("test", "x", 1));
+        assert!(reference != challenge("other test", "x", 1));
+        assert!(reference != challenge("test", "y", 1));
+        assert!(reference != challenge("test", "x", 2));
+    }
+
+    #[test]
+    fn splitting_bytes_differently_changes_the_challenge() {
+        let mut transcript = Transcript::<BnEngine>::new("test");
+        transcript.append_bytes("a", b"bc");
+        let mut other_transcript = Transcript::<BnEngine>::new("test");
+        other_transcript.append_bytes("ab", b"c");
+
+        assert!(
+            transcript.challenge_field_element("challenge")
+                != other_transcript.challenge_field_element("challenge")
+        );
+    }
+}
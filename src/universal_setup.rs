@@ -0,0 +1,373 @@
+// One ceremony for many statements. The powers of s and their alpha-shifted copies do not depend
+// on the target polynomial, so a single ceremony output can be specialized into the keys of every
+// statement whose target has at most the ceremony's degree. Statements of a much lower degree can
+// use a truncated copy of the ceremony instead, so their provers do not carry every power of s.
+
+use crate::{
+    crs_player::{
+        validate_ceremony_finalized_by_beacon, validate_ceremony_transcript_of_degree,
+        CeremonyError,
+    },
+    pairing_engine::{BnEngine, EncodableEngine, PairingEngine},
+    prover::ProvablePolynomial,
+    CrsCeremonyStep, CrsCeremonyTranscript, CrsCeremonyValues, DegreeError, ProvingKey,
+    VerifyingKey, CRS, POLYNOMIAL_DEGREE,
+};
+
+/// The last accumulator of a validated ceremony, not yet bound to any target polynomial
+pub(crate) struct UniversalReferenceString<E: PairingEngine = BnEngine> {
+    crs: CRS<E>,
+}
+
+impl<E: PairingEngine> UniversalReferenceString<E> {
+    /// Validates the whole transcript, as every statement will rely on it
+    pub(crate) fn from_ceremony(
+        transcript: CrsCeremonyTranscript<E>,
+    ) -> Result<Self, CeremonyError> {
+        return Self::from_ceremony_of_degree(transcript, POLYNOMIAL_DEGREE as usize);
+    }
+
+    /// `from_ceremony` for a transcript of `truncate_ceremony_transcript` cut down to `degree`
+    pub(crate) fn from_ceremony_of_degree(
+        mut transcript: CrsCeremonyTranscript<E>,
+        degree: usize,
+    ) -> Result<Self, CeremonyError> {
+        validate_ceremony_transcript_of_degree(&transcript, degree)?;
+        let last = transcript
+            .history
+            .pop()
+            .ok_or(CeremonyError::EmptyTranscript)?;
+        return Ok(Self {
+            crs: last.accumulator,
+        });
+    }
+
+    /// `from_ceremony` for a ceremony whose last contribution is the one
+    /// `crs_player::finalize_ceremony_with_beacon` makes from `beacon`
+    pub(crate) fn from_ceremony_finalized_by_beacon(
+        mut transcript: CrsCeremonyTranscript<E>,
+        beacon: &[u8],
+    ) -> Result<Self, CeremonyError>
+    where
+        E: EncodableEngine,
+    {
+        validate_ceremony_finalized_by_beacon(&transcript, beacon)?;
+        let last = transcript
+            .history
+            .pop()
+            .ok_or(CeremonyError::EmptyTranscript)?;
+        return Ok(Self {
+            crs: last.accumulator,
+        });
+    }
+
+    /// the highest degree of a target polynomial, or of a prover's polynomial, it can serve
+    pub(crate) fn max_degree(&self) -> usize {
+        return self.crs.encrypted1_s_powers.len() - 1;
+    }
+
+    /// The keys of the statement "I know a multiple of `target_polynomial`"
+    pub(crate) fn specialize<P: ProvablePolynomial<E>>(
+        &self,
+        target_polynomial: P,
+    ) -> Result<(ProvingKey<P, E>, VerifyingKey<E>), DegreeError> {
+        let verifying_key = VerifyingKey::for_target(&target_polynomial, &self.crs)?;
+        let proving_key = ProvingKey::for_target(target_polynomial, &self.crs);
+        return Ok((proving_key, verifying_key));
+    }
+}
+
+/// The ceremony as if it had been run for `degree` only: every accumulator and step keeps its powers
+/// of s up to `degree`. The ceremony's relations hold power by power, so the truncated transcript
+/// passes `validate_ceremony_transcript_of_degree` for `degree` on its own, and proves its last
+/// accumulator, the smaller CRS, came out of the same contributions.
+pub(crate) fn truncate_ceremony_transcript<E: PairingEngine>(
+    transcript: &CrsCeremonyTranscript<E>,
+    degree: usize,
+) -> Result<CrsCeremonyTranscript<E>, DegreeError> {
+    let crs_degree = transcript
+        .history
+        .last()
+        .and_then(|last| last.accumulator.encrypted1_s_powers.len().checked_sub(1))
+        .ok_or(DegreeError::EmptyCrs)?;
+    if degree == 0 || degree > crs_degree {
+        return Err(DegreeError::Truncation {
+            requested_degree: degree,
+            crs_degree,
+        });
+    }
+
+    let number_of_powers = degree + 1;
+    let history = transcript
+        .history
+        .iter()
+        .map(|ccv| CrsCeremonyValues {
+            accumulator: CRS {
+                encrypted2_alpha: ccv.accumulator.encrypted2_alpha,
+                encrypted2_s_powers: first_powers(
+                    &ccv.accumulator.encrypted2_s_powers,
+                    number_of_powers,
+                ),
+                encrypted1_s_powers: first_powers(
+                    &ccv.accumulator.encrypted1_s_powers,
+                    number_of_powers,
+                ),
+                encrypted1_alpha_times_s_powers: first_powers(
+                    &ccv.accumulator.encrypted1_alpha_times_s_powers,
+                    number_of_powers,
+                ),
+            },
+            step: CrsCeremonyStep {
+                encrypted1_s_powers: first_powers(&ccv.step.encrypted1_s_powers, number_of_powers),
+                encrypted1_alpha: ccv.step.encrypted1_alpha,
+                encrypted2_alpha_times_s_powers: first_powers(
+                    &ccv.step.encrypted2_alpha_times_s_powers,
+                    number_of_powers,
+                ),
+            },
+        })
+        .collect();
+    return Ok(CrsCeremonyTranscript { history });
+}
+
+/// a malformed transcript may have fewer, which then fails validation rather than panicking here
+fn first_powers<G: Copy>(powers: &[G], number_of_powers: usize) -> Vec<G> {
+    return powers.iter().take(number_of_powers).copied().collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use polynomen::Poly;
+
+    use super::*;
+    use crate::{
+        crs_player::{finalize_ceremony_with_beacon, validate_ceremony_transcript, CrsPlayer},
+        prover::Prover,
+        toy_engine::ToyEngine,
+        verificiation::verify,
+        ProofError,
+    };
+
+    fn ceremony() -> CrsCeremonyTranscript<ToyEngine> {
+        let mut transcript = CrsCeremonyTranscript {
+            history: vec![CrsPlayer::with_engine(12, 13).start_crs_ceremony()],
+        };
+        let next = CrsPlayer::with_engine(14, 15)
+            .continue_crs_ceremony(&transcript)
+            .expect("honest transcript");
+        transcript.history.push(next);
+        return transcript;
+    }
+
+    fn reference_string() -> UniversalReferenceString<ToyEngine> {
+        return UniversalReferenceString::from_ceremony(ceremony()).expect("honest transcript");
+    }
+
+    #[test]
+    fn two_targets_are_proven_with_one_ceremony() {
+        let reference_string = reference_string();
+        let first_target = Poly::new_from_coeffs(&[3, 4, 5]);
+        let second_target = Poly::new_from_coeffs(&[1, 2]);
+        let (first_proving_key, first_verifying_key) = reference_string
+            .specialize(first_target.clone())
+            .expect("degree within the CRS");
+        let (second_proving_key, second_verifying_key) = reference_string
+            .specialize(second_target.clone())
+            .expect("degree within the CRS");
+
+        let first_proof = Prover::new(&first_target * &Poly::new_from_coeffs(&[6, 7]))
+            .prove(&first_proving_key)
+            .expect("degree within the CRS");
+        let second_proof = Prover::new(&second_target * &Poly::new_from_coeffs(&[8, 9, 10]))
+            .prove(&second_proving_key)
+            .expect("degree within the CRS");
+
+        assert!(verify(&first_verifying_key, &first_proof));
+        assert!(verify(&second_verifying_key, &second_proof));
+
+        // each proof is for its own statement only
+        assert!(!verify(&second_verifying_key, &first_proof));
+        assert!(!verify(&first_verifying_key, &second_proof));
+    }
+
+    #[test]
+    fn target_beyond_the_ceremony_degree_is_refused() {
+        let reference_string = reference_string();
+        assert_eq!(reference_string.max_degree(), 5);
+
+        let too_high = Poly::new_from_coeffs(&[1, 1, 1, 1, 1, 1, 1]);
+        assert_eq!(
+            reference_string.specialize(too_high).err(),
+            Some(DegreeError::TargetPolynomialExceedsCrs {
+                target_degree: 6,
+                crs_degree: 5
+            })
+        );
+    }
+
+    #[test]
+    fn truncated_ceremony_validates_and_serves_smaller_statements() {
+        let mut transcript = ceremony();
+        let truncated =
+            truncate_ceremony_transcript(&transcript, 2).expect("degree within the CRS");
+        assert_eq!(
+            validate_ceremony_transcript_of_degree(&truncated, 2),
+            Ok(())
+        );
+        // only when it is expected to be that short
+        assert_eq!(
+            validate_ceremony_transcript(&truncated),
+            Err(CeremonyError::WrongNumberOfPowers { contribution: 0 })
+        );
+
+        let full_crs = transcript.history.pop().expect("non-empty").accumulator;
+        let reference_string = UniversalReferenceString::from_ceremony_of_degree(truncated, 2)
+            .expect("valid truncation");
+        assert_eq!(reference_string.max_degree(), 2);
+        assert!(reference_string.crs.encrypted1_s_powers == full_crs.encrypted1_s_powers[..3]);
+
+        let target = Poly::new_from_coeffs(&[3, 4]);
+        let (proving_key, verifying_key) = reference_string
+            .specialize(target.clone())
+            .expect("degree within the CRS");
+        let proof = Prover::new(&target * &Poly::new_from_coeffs(&[6, 7]))
+            .prove(&proving_key)
+            .expect("degree within the CRS");
+        assert!(verify(&verifying_key, &proof));
+
+        assert_eq!(
+            Prover::new(&target * &Poly::new_from_coeffs(&[6, 7, 8]))
+                .prove(&proving_key)
+                .err(),
+            Some(ProofError::Degree(
+                DegreeError::SecretPolynomialExceedsCrs {
+                    polynomial_degree: 3,
+                    crs_degree: 2
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn truncation_beyond_the_ceremony_degree_or_to_zero_is_refused() {
+        let transcript = ceremony();
+        for requested_degree in [0, 6] {
+            assert_eq!(
+                truncate_ceremony_transcript(&transcript, requested_degree).err(),
+                Some(DegreeError::Truncation {
+                    requested_degree,
+                    crs_degree: 5
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn truncation_of_an_empty_ceremony_is_refused() {
+        let transcript: CrsCeremonyTranscript<ToyEngine> =
+            CrsCeremonyTranscript { history: vec![] };
+        assert_eq!(
+            truncate_ceremony_transcript(&transcript, 2).err(),
+            Some(DegreeError::EmptyCrs)
+        );
+    }
+
+    #[test]
+    fn tampered_truncation_is_rejected() {
+        let mut truncated =
+            truncate_ceremony_transcript(&ceremony(), 2).expect("degree within the CRS");
+        let accumulator = &mut truncated.history[1].accumulator;
+        accumulator.encrypted2_s_powers[2] =
+            ToyEngine::homomorphic2_multiply(&accumulator.encrypted2_s_powers[2], 2);
+
+        assert_eq!(
+            validate_ceremony_transcript_of_degree(&truncated, 2),
+            Err(CeremonyError::G1AndG2PowersMismatch { contribution: 1 })
+        );
+    }
+
+    #[test]
+    fn truncated_ceremony_can_be_continued() {
+        let mut truncated =
+            truncate_ceremony_transcript(&ceremony(), 2).expect("degree within the CRS");
+        let player = CrsPlayer::with_engine(16, 17);
+        assert_eq!(
+            player.continue_crs_ceremony(&truncated).err(),
+            Some(CeremonyError::WrongNumberOfPowers { contribution: 0 })
+        );
+
+        let next = player
+            .continue_crs_ceremony_of_degree(&truncated, 2)
+            .expect("valid truncation");
+        assert_eq!(next.accumulator.encrypted1_s_powers.len(), 3);
+        assert_eq!(next.step.encrypted1_s_powers.len(), 3);
+        truncated.history.push(next);
+        assert_eq!(
+            validate_ceremony_transcript_of_degree(&truncated, 2),
+            Ok(())
+        );
+
+        let reference_string = UniversalReferenceString::from_ceremony_of_degree(truncated, 2)
+            .expect("valid continuation");
+        let target = Poly::new_from_coeffs(&[3, 4]);
+        let (proving_key, verifying_key) = reference_string
+            .specialize(target.clone())
+            .expect("degree within the CRS");
+        let proof = Prover::new(&target * &Poly::new_from_coeffs(&[6, 7]))
+            .prove(&proving_key)
+            .expect("degree within the CRS");
+        assert!(verify(&verifying_key, &proof));
+    }
+
+    #[test]
+    fn ceremony_is_finalized_only_by_the_contribution_of_its_beacon() {
+        let finalized = |beacon: &[u8]| {
+            let mut transcript = ceremony();
+            let beacon_contribution =
+                finalize_ceremony_with_beacon(&transcript, beacon).expect("honest transcript");
+            transcript.history.push(beacon_contribution);
+            return transcript;
+        };
+
+        assert!(UniversalReferenceString::from_ceremony_finalized_by_beacon(
+            finalized(b"block 1"),
+            b"block 1"
+        )
+        .is_ok());
+        assert_eq!(
+            UniversalReferenceString::from_ceremony_finalized_by_beacon(
+                finalized(b"block 1"),
+                b"block 2"
+            )
+            .err(),
+            Some(CeremonyError::BeaconContributionMismatch)
+        );
+        // a player's own contribution, whose secrets nobody can recompute, doesn't finalize it
+        assert_eq!(
+            UniversalReferenceString::from_ceremony_finalized_by_beacon(ceremony(), b"block 1")
+                .err(),
+            Some(CeremonyError::BeaconContributionMismatch)
+        );
+    }
+
+    #[test]
+    fn invalid_ceremony_is_refused() {
+        let transcript: CrsCeremonyTranscript<ToyEngine> = CrsCeremonyTranscript {
+            history: vec![CrsPlayer::with_engine(12, 0).start_crs_ceremony()],
+        };
+        assert_eq!(
+            UniversalReferenceString::from_ceremony(transcript).err(),
+            Some(CeremonyError::DegenerateSecret { contribution: 0 })
+        );
+    }
+
+    #[test]
+    fn empty_ceremony_is_refused() {
+        let transcript: CrsCeremonyTranscript<ToyEngine> =
+            CrsCeremonyTranscript { history: vec![] };
+        assert_eq!(
+            UniversalReferenceString::from_ceremony(transcript).err(),
+            Some(CeremonyError::EmptyTranscript)
+        );
+    }
+}
@@ -1,22 +1,812 @@
+use zksnark::{field::FieldIdentity, groth16::FrLocal};
+
 use crate::{
-    cryptography::{crypto_pairing, encrypt2, homomorphic2_eval_polynomial},
-    PublicData,
+    cryptography::{
+        homomorphic1_linear_combination, homomorphic2_linear_combination, negate1,
+        pairing_product_is_one,
+    },
+    pairing_engine::{BnEngine, PairingEngine},
+    prover::ProvablePolynomial,
+    trace, ComputationProof, ComputationVerifyingKey, DegreeError, Proof, PublicData, VerifyingKey,
+    CRS,
 };
 
-pub(crate) fn verify(public: &PublicData, proof: &crate::Proof) -> bool {
-    let encrypted_target_poly_at_s =
-        homomorphic2_eval_polynomial(&public.crs.encrypted2_s_powers, &public.target_polynomial);
+impl<E: PairingEngine> VerifyingKey<E> {
+    pub(crate) fn new<P: ProvablePolynomial<E>>(
+        public: &PublicData<P, E>,
+    ) -> Result<Self, DegreeError> {
+        return Self::for_target(&public.target_polynomial, &public.crs);
+    }
+
+    /// Refuses a target polynomial of higher degree than the CRS, which it could not evaluate at s
+    pub(crate) fn for_target<P: ProvablePolynomial<E>>(
+        target_polynomial: &P,
+        crs: &CRS<E>,
+    ) -> Result<Self, DegreeError> {
+        let target_degree = target_polynomial.degree();
+        let crs_degree = crs
+            .encrypted2_s_powers
+            .len()
+            .checked_sub(1)
+            .ok_or(DegreeError::EmptyCrs)?;
+        if target_degree > crs_degree {
+            return Err(DegreeError::TargetPolynomialExceedsCrs {
+                target_degree,
+                crs_degree,
+            });
+        }
+
+        return Ok(Self {
+            encrypted2_target_poly_at_s: target_polynomial
+                .homomorphic2_eval(&crs.encrypted2_s_powers)?,
+            encrypted2_alpha: crs.encrypted2_alpha,
+            encrypted2_one: E::generator2(),
+        });
+    }
+}
+
+/// The two equations `verify` checks
+#[derive(Debug, PartialEq)]
+pub(crate) enum VerificationCheck {
+    /// `e(p, g) == e(h, t)`, section 3.2 of the paper
+    PolynomialHasTargetRoots,
+    /// `e(p, alpha) == e(alpha p, g)`, section 3.4
+    PolynomialWasEvaluatedOnCrs,
+}
+
+pub(crate) fn verify<E: PairingEngine>(key: &VerifyingKey<E>, proof: &Proof<E>) -> bool {
+    return failed_checks(key, proof).is_empty();
+}
+
+/// Which of the equations of `verify` the proof does not satisfy, every one is checked
+pub(crate) fn failed_checks<E: PairingEngine>(
+    key: &VerifyingKey<E>,
+    proof: &Proof<E>,
+) -> Vec<VerificationCheck> {
+    // e(p, g) == e(h, t)
+    let provers_secret_poly_has_roots_of_target_poly = E::pairing_product_is_one(&[
+        (proof.encrypted1_secret_poly_at_s, key.encrypted2_one),
+        (
+            E::negate1(proof.encrypted1_ratio_poly_at_s),
+            key.encrypted2_target_poly_at_s,
+        ),
+    ]);
+    trace::note(
+        "3.6.1",
+        "Verifier: the polynomial has the target's roots",
+        "A pairing moves both products into the target group, where p(s) = h(s) t(s) is checked as \
+         e(g^p, g) == e(g^h, g^t(s)) without decrypting anything.",
+        || {
+            vec![
+                (
+                    "left",
+                    format!(
+                        "e({}, {})",
+                        E::describe1(&proof.encrypted1_secret_poly_at_s),
+                        E::describe2(&key.encrypted2_one)
+                    ),
+                ),
+                (
+                    "right",
+                    format!(
+                        "e({}, {})",
+                        E::describe1(&proof.encrypted1_ratio_poly_at_s),
+                        E::describe2(&key.encrypted2_target_poly_at_s)
+                    ),
+                ),
+                ("holds", provers_secret_poly_has_roots_of_target_poly.to_string()),
+            ]
+        },
+    );
+
+    // e(p, alpha) == e(alpha p, g)
+    let proof_was_created_only_by_polynomial_evaluation_only_of_restricted_degree =
+        E::pairing_product_is_one(&[
+            (proof.encrypted1_secret_poly_at_s, key.encrypted2_alpha),
+            (
+                E::negate1(proof.encrypted1_alpha_times_secret_poly_at_s),
+                key.encrypted2_one,
+            ),
+        ]);
+    trace::note(
+        "3.6.1",
+        "Verifier: the polynomial was evaluated on the CRS",
+        "The alpha-shifted value must be alpha times the other one, checked as \
+         e(g^p, g^alpha) == e(g^(alpha p), g).",
+        || {
+            vec![
+                (
+                    "left",
+                    format!(
+                        "e({}, {})",
+                        E::describe1(&proof.encrypted1_secret_poly_at_s),
+                        E::describe2(&key.encrypted2_alpha)
+                    ),
+                ),
+                (
+                    "right",
+                    format!(
+                        "e({}, {})",
+                        E::describe1(&proof.encrypted1_alpha_times_secret_poly_at_s),
+                        E::describe2(&key.encrypted2_one)
+                    ),
+                ),
+                (
+                    "holds",
+                    proof_was_created_only_by_polynomial_evaluation_only_of_restricted_degree
+                        .to_string(),
+                ),
+            ]
+        },
+    );
+
+    let mut failed_checks = vec![];
+    if !provers_secret_poly_has_roots_of_target_poly {
+        failed_checks.push(VerificationCheck::PolynomialHasTargetRoots);
+    }
+    if !proof_was_created_only_by_polynomial_evaluation_only_of_restricted_degree {
+        failed_checks.push(VerificationCheck::PolynomialWasEvaluatedOnCrs);
+    }
+    return failed_checks;
+}
+
+/// A verifier that ran a one-player ceremony itself, so it still knows `s` and `alpha`.
+///
+/// It checks the same two equations as `verify` with scalar multiplications in G1 instead of
+/// pairings, but only this verifier can check, as nobody else may learn the secrets.
+pub(crate) struct DesignatedVerifier<E: PairingEngine = BnEngine> {
+    target_poly_at_s: E::Scalar,
+    alpha: E::Scalar,
+}
+
+impl<E: PairingEngine> DesignatedVerifier<E> {
+    pub(crate) fn new<P: ProvablePolynomial<E>>(
+        target_polynomial: &P,
+        s: usize,
+        alpha: usize,
+    ) -> Self {
+        let s = E::scalar(s);
+        Self {
+            target_poly_at_s: target_polynomial
+                .coefficients()
+                .into_iter()
+                .rev()
+                .fold(E::scalar(0), |acc, coefficient| acc * s + coefficient),
+            alpha: E::scalar(alpha),
+        }
+    }
+
+    pub(crate) fn verify(&self, proof: &Proof<E>) -> bool {
+        // g^p == (g^h)^t(s)
+        let provers_secret_poly_has_roots_of_target_poly =
+            E::multiply1(proof.encrypted1_ratio_poly_at_s, self.target_poly_at_s)
+                == proof.encrypted1_secret_poly_at_s;
+
+        // (g^p)^alpha == g^(alpha p)
+        let proof_was_created_only_by_polynomial_evaluation =
+            E::multiply1(proof.encrypted1_secret_poly_at_s, self.alpha)
+                == proof.encrypted1_alpha_times_secret_poly_at_s;
+
+        return provers_secret_poly_has_roots_of_target_poly
+            && proof_was_created_only_by_polynomial_evaluation;
+    }
+}
+
+/// Verifies a proof of a computation, the checks of section 4.9 of the paper.
+///
+/// `public_inputs` are the values of the public variables, in the order their indices were given to
+/// `CrsPlayer::create_computation_keys`.
+pub(crate) fn verify_computation(
+    key: &ComputationVerifyingKey,
+    public_inputs: &[FrLocal],
+    proof: &ComputationProof,
+) -> bool {
+    if public_inputs.len() + 1 != key.encrypted1_left_public_variable_polys_at_s.len() {
+        return false;
+    }
+
+    // e(L, alpha_l) == e(alpha_l L, g)
+    let left_operand_is_a_combination_of_left_variable_polys = pairing_product_is_one(&[
+        (proof.encrypted1_left_poly_at_s, key.encrypted2_alpha_left),
+        (
+            negate1(proof.encrypted1_alpha_times_left_poly_at_s),
+            key.encrypted2_one,
+        ),
+    ]);
+
+    // e(alpha_r, R) == e(g, alpha_r R)
+    let right_operand_is_a_combination_of_right_variable_polys = pairing_product_is_one(&[
+        (key.encrypted1_alpha_right, proof.encrypted2_right_poly_at_s),
+        (
+            negate1(key.encrypted1_one),
+            proof.encrypted2_alpha_times_right_poly_at_s,
+        ),
+    ]);
+
+    // e(O, alpha_o) == e(alpha_o O, g)
+    let output_is_a_combination_of_output_variable_polys = pairing_product_is_one(&[
+        (
+            proof.encrypted1_output_poly_at_s,
+            key.encrypted2_alpha_output,
+        ),
+        (
+            negate1(proof.encrypted1_alpha_times_output_poly_at_s),
+            key.encrypted2_one,
+        ),
+    ]);
 
-    let provers_secret_poly_has_roots_of_target_poly =
-        crypto_pairing(proof.encrypted1_secret_poly_at_s, encrypt2(1))
-            == crypto_pairing(proof.encrypted1_ratio_poly_at_s, encrypted_target_poly_at_s);
+    // e(L + O, beta gamma) * e(beta gamma, R) == e(Z, gamma)
+    let same_variable_values_were_used_in_every_operand = pairing_product_is_one(&[
+        (
+            proof.encrypted1_left_poly_at_s + proof.encrypted1_output_poly_at_s,
+            key.encrypted2_beta_times_gamma,
+        ),
+        (
+            key.encrypted1_beta_times_gamma,
+            proof.encrypted2_right_poly_at_s,
+        ),
+        (
+            negate1(proof.encrypted1_variable_consistency_poly_at_s),
+            key.encrypted2_gamma,
+        ),
+    ]);
 
-    let proof_was_created_only_by_polynomial_evaluation_only_of_restricted_degree = crypto_pairing(
-        proof.encrypted1_secret_poly_at_s,
-        public.crs.encrypted2_alpha,
+    // section 4.9: the verifier evaluates the public part of each operand itself
+    let public_values: Vec<FrLocal> = std::iter::once(FrLocal::one())
+        .chain(public_inputs.iter().copied())
+        .collect();
+    // a key whose operands have a different number of public variables is malformed
+    let (
+        Ok(encrypted1_left_public_poly_at_s),
+        Ok(encrypted2_right_public_poly_at_s),
+        Ok(encrypted1_output_public_poly_at_s),
+    ) = (
+        homomorphic1_linear_combination(
+            &key.encrypted1_left_public_variable_polys_at_s,
+            &public_values,
+        ),
+        homomorphic2_linear_combination(
+            &key.encrypted2_right_public_variable_polys_at_s,
+            &public_values,
+        ),
+        homomorphic1_linear_combination(
+            &key.encrypted1_output_public_variable_polys_at_s,
+            &public_values,
+        ),
     )
-        == crypto_pairing(proof.encrypted1_alpha_times_secret_poly_at_s, encrypt2(1));
+    else {
+        return false;
+    };
+    let encrypted1_left_poly_at_s =
+        proof.encrypted1_left_poly_at_s + encrypted1_left_public_poly_at_s;
+    let encrypted2_right_poly_at_s =
+        proof.encrypted2_right_poly_at_s + encrypted2_right_public_poly_at_s;
+    let encrypted1_output_poly_at_s =
+        proof.encrypted1_output_poly_at_s + encrypted1_output_public_poly_at_s;
+
+    // e(L, R) == e(h, t) * e(O, g)
+    let every_operation_holds = pairing_product_is_one(&[
+        (encrypted1_left_poly_at_s, encrypted2_right_poly_at_s),
+        (
+            negate1(proof.encrypted1_ratio_poly_at_s),
+            key.encrypted2_target_poly_at_s,
+        ),
+        (negate1(encrypted1_output_poly_at_s), key.encrypted2_one),
+    ]);
+
+    return left_operand_is_a_combination_of_left_variable_polys
+        && right_operand_is_a_combination_of_right_variable_polys
+        && output_is_a_combination_of_output_variable_polys
+        && same_variable_values_were_used_in_every_operand
+        && every_operation_holds;
+}
+
+/// Verifies many proofs of the same statement with a constant number of pairings.
+///
+/// Both equations of `verify`, for every proof, are folded into one pairing product using random
+/// weights, so a batch that contains an invalid proof passes with probability about `2^-64`.
+/// When the batch fails it is bisected to find the failing proofs, whose indices are returned.
+pub(crate) fn verify_batch<E: PairingEngine>(
+    key: &VerifyingKey<E>,
+    proofs: &[Proof<E>],
+) -> Result<(), Vec<usize>> {
+    let failed_indices =
+        find_failing_proofs(key, proofs, &(0..proofs.len()).collect::<Vec<usize>>());
+
+    if failed_indices.is_empty() {
+        return Ok(());
+    }
+    return Err(failed_indices);
+}
+
+fn find_failing_proofs<E: PairingEngine>(
+    key: &VerifyingKey<E>,
+    proofs: &[Proof<E>],
+    indices: &[usize],
+) -> Vec<usize> {
+    if indices.is_empty() || randomly_weighted_batch_is_valid(key, proofs, indices) {
+        return vec![];
+    }
+    if indices.len() == 1 {
+        return indices.to_vec();
+    }
+
+    let (left, right) = indices.split_at(indices.len() / 2);
+    let mut failed_indices = find_failing_proofs(key, proofs, left);
+    failed_indices.extend(find_failing_proofs(key, proofs, right));
+    return failed_indices;
+}
+
+/// With weights `r_i` for the first equation and `q_i` for the second, checks
+/// `e(sum r_i p_i - sum q_i alpha_p_i, g) * e(-sum r_i h_i, t) * e(sum q_i p_i, alpha) == 1`
+fn randomly_weighted_batch_is_valid<E: PairingEngine>(
+    key: &VerifyingKey<E>,
+    proofs: &[Proof<E>],
+    indices: &[usize],
+) -> bool {
+    let roots_weights: Vec<usize> = indices.iter().map(|_| random_nonzero_weight()).collect();
+    let alpha_weights: Vec<usize> = indices.iter().map(|_| random_nonzero_weight()).collect();
+
+    let secret_polys: Vec<E::G1> = indices
+        .iter()
+        .map(|i| proofs[*i].encrypted1_secret_poly_at_s)
+        .collect();
+    let ratio_polys: Vec<E::G1> = indices
+        .iter()
+        .map(|i| proofs[*i].encrypted1_ratio_poly_at_s)
+        .collect();
+    let alpha_times_secret_polys: Vec<E::G1> = indices
+        .iter()
+        .map(|i| proofs[*i].encrypted1_alpha_times_secret_poly_at_s)
+        .collect();
+
+    let weighted_secret_polys = E::multi_scalar_multiply1(&secret_polys, &roots_weights);
+    let weighted_ratio_polys = E::multi_scalar_multiply1(&ratio_polys, &roots_weights);
+    let weighted_alpha_times_secret_polys =
+        E::multi_scalar_multiply1(&alpha_times_secret_polys, &alpha_weights);
+    let alpha_weighted_secret_polys = E::multi_scalar_multiply1(&secret_polys, &alpha_weights);
+
+    return E::pairing_product_is_one(&[
+        (
+            weighted_secret_polys - weighted_alpha_times_secret_polys,
+            key.encrypted2_one,
+        ),
+        (
+            E::negate1(weighted_ratio_polys),
+            key.encrypted2_target_poly_at_s,
+        ),
+        (alpha_weighted_secret_polys, key.encrypted2_alpha),
+    ]);
+}
+
+fn random_nonzero_weight() -> usize {
+    return rand::random::<usize>().max(1);
+}
+
+#[cfg(test)]
+mod tests {
+    use polynomen::Poly;
+
+    use super::*;
+    use crate::{
+        circuit::{cubic_circuit, ArithmeticCircuit},
+        crs_player::{ComputationSecrets, CrsPlayer, PublicInputError},
+        pairing_engine::ZksnarkEngine,
+        prover::{ComputationProver, Prover},
+        qap::QuadraticArithmeticProgram,
+        ComputationProvingKey, ProofError, ProvingKey,
+    };
+
+    fn public_data() -> PublicData {
+        return PublicData {
+            target_polynomial: Poly::new_from_coeffs(&[3, 4, 5]),
+            crs: CrsPlayer::new(12, 13).start_crs_ceremony().accumulator,
+        };
+    }
+
+    fn honest_provers(public: &PublicData) -> Vec<Prover> {
+        return (1..6)
+            .map(|k| Prover::new(&public.target_polynomial * &Poly::new_from_coeffs(&[k, k + 1])))
+            .collect();
+    }
+
+    #[test]
+    fn batch_of_honest_proofs_is_valid() {
+        let public = public_data();
+        let proving_key = ProvingKey::new(&public);
+        let verifying_key = VerifyingKey::new(&public).expect("target degree within the CRS");
+        let proofs: Vec<Proof> = honest_provers(&public)
+            .iter()
+            .map(|prover| prover.prove(&proving_key).expect("degree within the CRS"))
+            .collect();
+
+        assert!(proofs.iter().all(|proof| verify(&verifying_key, proof)));
+        assert_eq!(verify_batch(&verifying_key, &proofs), Ok(()));
+        assert_eq!(verify_batch(&verifying_key, &[]), Ok(()));
+    }
+
+    #[test]
+    fn batch_reports_the_corrupted_proof() {
+        let public = public_data();
+        let proving_key = ProvingKey::new(&public);
+        let verifying_key = VerifyingKey::new(&public).expect("target degree within the CRS");
+        let proofs: Vec<Proof> = honest_provers(&public)
+            .iter()
+            .enumerate()
+            .map(|(index, prover)| {
+                if index == 3 {
+                    prover
+                        .erroneous_prove(&proving_key)
+                        .expect("degree within the CRS")
+                } else {
+                    prover.prove(&proving_key).expect("degree within the CRS")
+                }
+            })
+            .collect();
+
+        let individually_failed: Vec<usize> = (0..proofs.len())
+            .filter(|index| !verify(&verifying_key, &proofs[*index]))
+            .collect();
+
+        assert_eq!(individually_failed, vec![3]);
+        assert_eq!(
+            verify_batch(&verifying_key, &proofs),
+            Err(individually_failed)
+        );
+    }
+
+    #[test]
+    fn batch_reports_every_corrupted_proof() {
+        let public = public_data();
+        let proving_key = ProvingKey::new(&public);
+        let verifying_key = VerifyingKey::new(&public).expect("target degree within the CRS");
+        let provers = honest_provers(&public);
+        let proofs: Vec<Proof> = provers
+            .iter()
+            .map(|prover| {
+                prover
+                    .erroneous_prove(&proving_key)
+                    .expect("degree within the CRS")
+            })
+            .collect();
+
+        assert_eq!(
+            verify_batch(&verifying_key, &proofs),
+            Err(vec![0, 1, 2, 3, 4])
+        );
+    }
+
+    #[test]
+    fn prover_refuses_a_polynomial_beyond_the_crs_degree() {
+        let public = public_data();
+        let proving_key = ProvingKey::new(&public);
+
+        let highest_degree = &public.target_polynomial * &Poly::new_from_coeffs(&[1, 1, 1, 1]);
+        let proof = Prover::new(highest_degree)
+            .prove(&proving_key)
+            .expect("degree 5 is within the CRS");
+        assert!(verify(
+            &VerifyingKey::new(&public).expect("target degree within the CRS"),
+            &proof
+        ));
+
+        let too_high = &public.target_polynomial * &Poly::new_from_coeffs(&[1, 1, 1, 1, 1]);
+        assert_eq!(
+            Prover::new(too_high).prove(&proving_key).err(),
+            Some(ProofError::Degree(
+                DegreeError::SecretPolynomialExceedsCrs {
+                    polynomial_degree: 6,
+                    crs_degree: 5
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn verifier_refuses_a_target_beyond_the_crs_degree() {
+        let public = PublicData {
+            target_polynomial: Poly::new_from_coeffs(&[1, 2, 3, 4, 5, 6, 7]),
+            crs: public_data().crs,
+        };
+
+        assert_eq!(
+            VerifyingKey::new(&public).err(),
+            Some(DegreeError::TargetPolynomialExceedsCrs {
+                target_degree: 6,
+                crs_degree: 5
+            })
+        );
+    }
+
+    #[test]
+    fn empty_crs_is_refused() {
+        let mut public = public_data();
+        public.crs.encrypted1_s_powers.clear();
+        public.crs.encrypted1_alpha_times_s_powers.clear();
+        public.crs.encrypted2_s_powers.clear();
+
+        assert_eq!(
+            Prover::new(Poly::new_from_coeffs(&[1]))
+                .prove(&ProvingKey::new(&public))
+                .err(),
+            Some(ProofError::Degree(DegreeError::EmptyCrs))
+        );
+        assert_eq!(
+            VerifyingKey::new(&public).err(),
+            Some(DegreeError::EmptyCrs)
+        );
+    }
+
+    fn computation_keys(
+        qap: &QuadraticArithmeticProgram,
+        public_inputs: &[usize],
+    ) -> (ComputationProvingKey, ComputationVerifyingKey) {
+        return CrsPlayer::<ZksnarkEngine>::with_engine(12, 13)
+            .create_computation_keys(qap, public_inputs, &computation_secrets())
+            .expect("valid public inputs");
+    }
+
+    fn computation_secrets() -> ComputationSecrets {
+        return ComputationSecrets {
+            alpha_l: 14,
+            alpha_r: 15,
+            alpha_o: 16,
+            beta: 17,
+            gamma: 18,
+            rho_l: 19,
+            rho_r: 20,
+        };
+    }
+
+    #[test]
+    fn honest_computation_proof_is_valid() {
+        let circuit = cubic_circuit();
+        let qap = circuit.compile();
+        let (proving_key, verifying_key) = computation_keys(&qap, &[]);
+
+        let prover = ComputationProver::new(
+            &qap,
+            circuit
+                .compute_witness(&[("x", 3)])
+                .expect("x is the circuit's input"),
+        );
+
+        assert!(verify_computation(
+            &verifying_key,
+            &[],
+            &prover.prove(&proving_key).expect("a satisfying witness")
+        ));
+    }
+
+    #[test]
+    fn computation_prover_refuses_an_unsatisfying_witness() {
+        let circuit = cubic_circuit();
+        let qap = circuit.compile();
+        let (proving_key, _) = computation_keys(&qap, &[]);
+
+        let mut witness = circuit
+            .compute_witness(&[("x", 3)])
+            .expect("x is the circuit's input");
+        witness[circuit.variable_index("out").expect("variable")] = FrLocal::from(31);
+        let prover = ComputationProver::new(&qap, witness);
+
+        assert_eq!(
+            prover.prove(&proving_key).err(),
+            Some(ProofError::NotAMultipleOfTarget)
+        );
+    }
+
+    #[test]
+    fn computation_prover_refuses_a_key_with_too_few_powers() {
+        let circuit = cubic_circuit();
+        let qap = circuit.compile();
+        let (mut proving_key, _) = computation_keys(&qap, &[]);
+        let prover = ComputationProver::new(
+            &qap,
+            circuit
+                .compute_witness(&[("x", 3)])
+                .expect("x is the circuit's input"),
+        );
+
+        // h(x) has degree 1 for the three operations of the cubic circuit
+        proving_key.encrypted1_s_powers.truncate(1);
+        assert_eq!(
+            prover.prove(&proving_key).err(),
+            Some(ProofError::Degree(
+                DegreeError::SecretPolynomialExceedsCrs {
+                    polynomial_degree: 1,
+                    crs_degree: 0
+                }
+            ))
+        );
+
+        proving_key.encrypted1_s_powers.clear();
+        assert_eq!(
+            prover.prove(&proving_key).err(),
+            Some(ProofError::Degree(DegreeError::EmptyCrs))
+        );
+    }
+
+    #[test]
+    fn computation_prover_refuses_a_key_for_a_larger_qap() {
+        let (proving_key, _) = computation_keys(&cubic_circuit().compile(), &[]);
+
+        let mut circuit = ArithmeticCircuit::new();
+        circuit.multiply("x", "x", "x_squared");
+        let prover = ComputationProver::new(
+            &circuit.compile(),
+            circuit
+                .compute_witness(&[("x", 3)])
+                .expect("x is the circuit's input"),
+        );
+
+        assert_eq!(
+            prover.prove(&proving_key).err(),
+            Some(ProofError::WitnessTooShort {
+                variable: 3,
+                witness_length: 3
+            })
+        );
+    }
+
+    #[test]
+    fn computation_proof_with_operands_from_different_witnesses_is_invalid() {
+        let circuit = cubic_circuit();
+        let qap = circuit.compile();
+        let (proving_key, verifying_key) = computation_keys(&qap, &[]);
+
+        let proof_for_3 = ComputationProver::new(
+            &qap,
+            circuit
+                .compute_witness(&[("x", 3)])
+                .expect("x is the circuit's input"),
+        )
+        .prove(&proving_key)
+        .expect("a satisfying witness");
+        let proof_for_4 = ComputationProver::new(
+            &qap,
+            circuit
+                .compute_witness(&[("x", 4)])
+                .expect("x is the circuit's input"),
+        )
+        .prove(&proving_key)
+        .expect("a satisfying witness");
+
+        let mixed_proof = ComputationProof {
+            encrypted2_right_poly_at_s: proof_for_4.encrypted2_right_poly_at_s,
+            encrypted2_alpha_times_right_poly_at_s: proof_for_4
+                .encrypted2_alpha_times_right_poly_at_s,
+            ..proof_for_3
+        };
+
+        assert!(!verify_computation(&verifying_key, &[], &mixed_proof));
+    }
+
+    #[test]
+    fn computation_proof_without_alpha_shift_is_invalid() {
+        let circuit = cubic_circuit();
+        let qap = circuit.compile();
+        let (proving_key, verifying_key) = computation_keys(&qap, &[]);
+
+        let proof = ComputationProver::new(
+            &qap,
+            circuit
+                .compute_witness(&[("x", 3)])
+                .expect("x is the circuit's input"),
+        )
+        .prove(&proving_key)
+        .expect("a satisfying witness");
+        let unshifted_proof = ComputationProof {
+            encrypted1_alpha_times_output_poly_at_s: proof.encrypted1_output_poly_at_s,
+            ..proof
+        };
+
+        assert!(!verify_computation(&verifying_key, &[], &unshifted_proof));
+    }
+
+    #[test]
+    fn computation_proof_is_valid_only_for_its_public_output() {
+        let mut circuit = ArithmeticCircuit::new();
+        circuit.multiply("x", "y", "out");
+        let qap = circuit.compile();
+        let out = circuit.variable_index("out").expect("variable");
+        let (proving_key, verifying_key) = computation_keys(&qap, &[out]);
+
+        let proof = ComputationProver::new(
+            &qap,
+            circuit
+                .compute_witness(&[("x", 6), ("y", 7)])
+                .expect("x and y are the circuit's inputs"),
+        )
+        .prove(&proving_key)
+        .expect("a satisfying witness");
+
+        assert!(verify_computation(
+            &verifying_key,
+            &[FrLocal::from(42)],
+            &proof
+        ));
+        assert!(!verify_computation(
+            &verifying_key,
+            &[FrLocal::from(43)],
+            &proof
+        ));
+        assert!(!verify_computation(&verifying_key, &[], &proof));
+    }
+
+    #[test]
+    fn invalid_public_inputs_are_refused() {
+        let qap = cubic_circuit().compile();
+        let number_of_variables = qap.number_of_variables();
+        let keys_with = |public_inputs: &[usize]| {
+            return CrsPlayer::<ZksnarkEngine>::with_engine(12, 13)
+                .create_computation_keys(&qap, public_inputs, &computation_secrets())
+                .err();
+        };
+
+        assert_eq!(keys_with(&[0]), Some(PublicInputError::ConstantOneListed));
+        assert_eq!(
+            keys_with(&[1, number_of_variables]),
+            Some(PublicInputError::VariableOutOfRange {
+                variable: number_of_variables,
+                number_of_variables
+            })
+        );
+        assert_eq!(
+            keys_with(&[2, 1, 2]),
+            Some(PublicInputError::DuplicateVariable { variable: 2 })
+        );
+    }
+
+    #[test]
+    fn designated_verifier_agrees_with_pairing_verification() {
+        let public = public_data();
+        let proving_key = ProvingKey::new(&public);
+        let verifying_key = VerifyingKey::new(&public).expect("target degree within the CRS");
+        let designated_verifier = DesignatedVerifier::new(&public.target_polynomial, 12, 13);
+
+        for prover in honest_provers(&public) {
+            let proof = prover.prove(&proving_key).expect("degree within the CRS");
+            assert!(verify(&verifying_key, &proof));
+            assert!(designated_verifier.verify(&proof));
+
+            let erroneous_proof = prover
+                .erroneous_prove(&proving_key)
+                .expect("degree within the CRS");
+            assert!(!verify(&verifying_key, &erroneous_proof));
+            assert!(!designated_verifier.verify(&erroneous_proof));
+        }
+    }
+
+    #[test]
+    fn designated_verifier_with_other_secrets_rejects_valid_proofs() {
+        let public = public_data();
+        let proof = honest_provers(&public)[0]
+            .prove(&ProvingKey::new(&public))
+            .expect("degree within the CRS");
+
+        assert!(!DesignatedVerifier::new(&public.target_polynomial, 12, 14).verify(&proof));
+        assert!(!DesignatedVerifier::new(&public.target_polynomial, 11, 13).verify(&proof));
+    }
+
+    #[test]
+    fn designated_verifier_checks_a_proof_for_the_target_of_a_qap() {
+        let circuit = cubic_circuit();
+        let qap = circuit.compile();
+        let witness = circuit
+            .compute_witness(&[("x", 3)])
+            .expect("x is the circuit's input");
+        let public = PublicData {
+            target_polynomial: qap.target_polynomial().clone(),
+            crs: CrsPlayer::<ZksnarkEngine>::with_engine(12, 13)
+                .start_crs_ceremony()
+                .accumulator,
+        };
+        let proof = Prover::new(qap.prover_polynomial(&witness))
+            .prove(&ProvingKey::new(&public))
+            .expect("degree within the CRS");
 
-    return provers_secret_poly_has_roots_of_target_poly
-        && proof_was_created_only_by_polynomial_evaluation_only_of_restricted_degree;
+        assert!(DesignatedVerifier::new(qap.target_polynomial(), 12, 13).verify(&proof));
+        assert!(!DesignatedVerifier::new(qap.target_polynomial(), 12, 14).verify(&proof));
+    }
 }